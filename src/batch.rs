@@ -0,0 +1,261 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+use crate::{anyhow, average_power, Result, Worker, WorkerProperty};
+
+///how long a [`Batch`] evolves the worker
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BatchLength {
+    Ticks(u64),
+    ///simulated time, rounded up to whole ticks
+    Time(f64),
+}
+
+impl BatchLength {
+    ///number of ticks to run a worker with `property`
+    pub fn ticks(&self, property: &WorkerProperty) -> Result<u64> {
+        match *self {
+            BatchLength::Ticks(n) => Ok(n),
+            BatchLength::Time(time) => {
+                if !(time >= 0. && time.is_finite()) {
+                    return Err(anyhow!(
+                        "simulated time should be non-negative, got {}",
+                        time
+                    ));
+                }
+                let tick = property.record_step as f64 * property.simu_step;
+                Ok((time / tick).ceil() as u64)
+            }
+        }
+    }
+}
+
+///headless run of a [`Worker`], writing into the `output` directory
+///- `observables.csv`, the detuning, average and peak power and temperature of each component
+///- `field{i}.csv`, the time domain field of component `i` as `re, im` pairs of each sample
+///- `final.json`, the [`Snapshot`](crate::Snapshot) at the end to resume the run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Batch {
+    pub length: BatchLength,
+    ///record every `every` ticks, the initial and final states are always recorded
+    pub every: u64,
+    pub output: PathBuf,
+}
+
+impl Batch {
+    pub fn new(length: BatchLength, output: impl Into<PathBuf>) -> Self {
+        Self {
+            length,
+            every: 1,
+            output: output.into(),
+        }
+    }
+    pub fn run(&self, worker: &mut Worker) -> Result<()> {
+        if self.every == 0 {
+            return Err(anyhow!("record interval should be positive"));
+        }
+        let ticks = self.length.ticks(worker.get_property())?;
+        std::fs::create_dir_all(&self.output)
+            .with_context(|| format!("creating output directory {}", self.output.display()))?;
+        let mut recorder = Recorder::create(&self.output, worker)?;
+        recorder.record(worker)?;
+        log::info!("running {} ticks into {}", ticks, self.output.display());
+        for i in 1..=ticks {
+            worker.tick();
+            if i.is_multiple_of(self.every) || i == ticks {
+                recorder.record(worker)?;
+            }
+            if i * 10 / ticks != (i - 1) * 10 / ticks {
+                log::info!(
+                    "{}% finished, simulated time {}",
+                    i * 100 / ticks,
+                    worker.time()
+                );
+            }
+        }
+        recorder.finish()?;
+        worker.save(self.output.join("final.json"))
+    }
+}
+
+struct Recorder {
+    observables: Output,
+    fields: Vec<Output>,
+}
+
+struct Output {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl Output {
+    fn create(path: PathBuf, header: &str) -> Result<Self> {
+        let file = File::create(&path).with_context(|| format!("creating {}", path.display()))?;
+        let mut output = Self {
+            path,
+            writer: BufWriter::new(file),
+        };
+        output.write(header)?;
+        Ok(output)
+    }
+    fn write(&mut self, line: &str) -> Result<()> {
+        writeln!(self.writer, "{}", line)
+            .with_context(|| format!("writing {}", self.path.display()))
+    }
+    fn finish(mut self) -> Result<()> {
+        self.writer
+            .flush()
+            .with_context(|| format!("writing {}", self.path.display()))
+    }
+}
+
+impl Recorder {
+    fn create(dir: &Path, worker: &Worker) -> Result<Self> {
+        let seed = worker.get_property().seed;
+        let mut header = format!("# seed {}\ntime", seed);
+        for c in worker.components() {
+            let i = c.index() + 1;
+            header += &format!(",detuning{0},power{0},peak{0},temperature{0}", i);
+        }
+        let observables = Output::create(dir.join("observables.csv"), &header)?;
+        let fields = worker
+            .components()
+            .iter()
+            .map(|c| {
+                let mut header = format!("# seed {}\ntime", seed);
+                for k in 0..worker.get_property().modes {
+                    header += &format!(",re{0},im{0}", k);
+                }
+                Output::create(dir.join(format!("field{}.csv", c.index() + 1)), &header)
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            observables,
+            fields,
+        })
+    }
+    fn record(&mut self, worker: &Worker) -> Result<()> {
+        let time = worker.time();
+        let states = worker.get_state();
+        let mut line = time.to_string();
+        for (&c, state) in worker.components().iter().zip(states.iter()) {
+            line += &format!(
+                ",{},{},{},{}",
+                worker.get_property().components[c.index()].alpha + worker.detuning_shift(c),
+                average_power(state),
+                state.iter().map(|x| x.norm_sqr()).fold(0., f64::max),
+                worker.temperature(c)
+            );
+        }
+        self.observables.write(&line)?;
+        for (output, state) in self.fields.iter_mut().zip(states) {
+            let mut line = time.to_string();
+            for x in state {
+                line += &format!(",{},{}", x.re, x.im);
+            }
+            output.write(&line)?;
+        }
+        Ok(())
+    }
+    fn finish(self) -> Result<()> {
+        self.observables.finish()?;
+        self.fields.into_iter().try_for_each(Output::finish)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Topology;
+
+    use super::*;
+
+    #[test]
+    fn ticks() {
+        let mut property = WorkerProperty::new(16, 0);
+        property.record_step = 4;
+        property.simu_step = 0.25;
+        let ticks = |length: BatchLength| length.ticks(&property);
+        assert_eq!(ticks(BatchLength::Ticks(7)).unwrap(), 7);
+        assert_eq!(ticks(BatchLength::Time(0.)).unwrap(), 0);
+        assert_eq!(ticks(BatchLength::Time(3.)).unwrap(), 3);
+        //partial ticks are rounded up
+        assert_eq!(ticks(BatchLength::Time(3.2)).unwrap(), 4);
+        assert_eq!(ticks(BatchLength::Time(0.1)).unwrap(), 1);
+        assert!(ticks(BatchLength::Time(-1.)).is_err());
+        assert!(ticks(BatchLength::Time(f64::NAN)).is_err());
+        assert!(ticks(BatchLength::Time(f64::INFINITY)).is_err());
+    }
+
+    #[test]
+    fn zero_interval() {
+        let output = std::env::temp_dir().join(format!("lle_batch_zero_{}", std::process::id()));
+        let mut batch = Batch::new(BatchLength::Ticks(1), &output);
+        batch.every = 0;
+        let mut worker = Worker::with_seed(Topology::Single, 16, 0);
+        let error = batch.run(&mut worker).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("record interval should be positive"));
+        assert!(!output.exists());
+    }
+
+    fn rows(lines: std::str::Lines) -> Vec<Vec<f64>> {
+        lines
+            .map(|l| l.split(',').map(|x| x.parse().unwrap()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn output_files() {
+        let modes = 8;
+        let output = std::env::temp_dir().join(format!("lle_batch_{}", std::process::id()));
+        let mut batch = Batch::new(BatchLength::Ticks(5), &output);
+        batch.every = 2;
+        let mut worker = Worker::with_seed(Topology::Coupled, modes, 7);
+        batch.run(&mut worker).unwrap();
+
+        let observables = std::fs::read_to_string(output.join("observables.csv")).unwrap();
+        let mut lines = observables.lines();
+        assert_eq!(lines.next(), Some("# seed 7"));
+        assert_eq!(
+            lines.next(),
+            Some("time,detuning1,power1,peak1,temperature1,detuning2,power2,peak2,temperature2")
+        );
+        //ticks 0, 2, 4 and the final tick 5
+        let records = rows(lines);
+        assert_eq!(records.len(), 4);
+        assert!(records.iter().all(|r| r.len() == 9));
+        assert_eq!(records[0][0], 0.);
+        assert_eq!(records[3][0], worker.time());
+
+        for i in 1..=2 {
+            let field = std::fs::read_to_string(output.join(format!("field{}.csv", i))).unwrap();
+            let mut lines = field.lines();
+            assert_eq!(lines.next(), Some("# seed 7"));
+            let header = lines.next().unwrap();
+            assert!(header.starts_with("time,re0,im0,re1,im1,"));
+            assert!(header.ends_with(",re7,im7"));
+            let records = rows(lines);
+            assert_eq!(records.len(), 4);
+            assert!(records.iter().all(|r| r.len() == 1 + 2 * modes));
+            //the last row is the final state
+            let state = worker.get_state()[i - 1];
+            for (k, x) in state.iter().enumerate() {
+                assert_eq!(records[3][1 + 2 * k], x.re);
+                assert_eq!(records[3][2 + 2 * k], x.im);
+            }
+        }
+
+        let loaded = Worker::load(output.join("final.json"));
+        std::fs::remove_dir_all(&output).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.get_property(), worker.get_property());
+        assert_eq!(loaded.time(), worker.time());
+        assert_eq!(loaded.get_state(), worker.get_state());
+    }
+}
@@ -0,0 +1,21 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Result};
+
+///value following the flag `name`, `None` if the flag is absent
+pub fn value<T: FromStr>(args: &[String], name: &str, usage: &str) -> Result<Option<T>>
+where
+    T::Err: Display,
+{
+    match args.iter().position(|x| x == name) {
+        Some(i) => {
+            let s = args
+                .get(i + 1)
+                .ok_or_else(|| anyhow!("missing value of {}\n{}", name, usage))?;
+            s.parse()
+                .map(Some)
+                .map_err(|e| anyhow!("illegal value '{}' of {}: {}", s, name, e))
+        }
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,52 @@
+use anyhow::{anyhow, Result};
+use lle_simulator::*;
+
+mod common;
+
+const USAGE: &str =
+    "usage: lle_batch <config.toml> (--ticks N | --time T) [--every N] [--output DIR] [--seed N]";
+
+fn main() -> Result<()> {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .parse_default_env()
+        .init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|x| x == "--help" || x == "-h") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let config_path = args
+        .first()
+        .filter(|x| !x.starts_with("--"))
+        .ok_or_else(|| anyhow!("missing configuration file\n{}", USAGE))?;
+    let mut config = Config::load(config_path)?;
+    if let Some(seed) = common::value(&args, "--seed", USAGE)? {
+        config.seed = Some(seed);
+    }
+    let length = match (
+        common::value(&args, "--ticks", USAGE)?,
+        common::value(&args, "--time", USAGE)?,
+    ) {
+        (Some(ticks), None) => BatchLength::Ticks(ticks),
+        (None, Some(time)) => BatchLength::Time(time),
+        (Some(_), Some(_)) => return Err(anyhow!("--ticks and --time are exclusive\n{}", USAGE)),
+        (None, None) => return Err(anyhow!("missing run length\n{}", USAGE)),
+    };
+    let mut batch = Batch::new(
+        length,
+        common::value::<String>(&args, "--output", USAGE)?.unwrap_or_else(|| "output".to_string()),
+    );
+    if let Some(every) = common::value(&args, "--every", USAGE)? {
+        batch.every = every;
+    }
+
+    let mut worker = config.build()?;
+    log::info!(
+        "simulating {:?} resonator with seed {}",
+        worker.topology(),
+        worker.get_property().seed
+    );
+    batch.run(&mut worker)
+}
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use lle_simulator::*;
+
+mod common;
+
+const USAGE: &str = "usage: lle_sweep <config.toml> --alpha START:END:STEPS --pump START:END:STEPS [--ticks N] [--window N] [--threads N] [--output DIR] [--seed N]";
+
+fn main() -> Result<()> {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .parse_default_env()
+        .init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|x| x == "--help" || x == "-h") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let config_path = args
+        .first()
+        .filter(|x| !x.starts_with("--"))
+        .ok_or_else(|| anyhow!("missing configuration file\n{}", USAGE))?;
+    let mut config = Config::load(config_path)?;
+    if let Some(seed) = common::value(&args, "--seed", USAGE)? {
+        config.seed = Some(seed);
+    }
+    let axis = |name: &str| -> Result<SweepAxis> {
+        common::value(&args, name, USAGE)?.ok_or_else(|| anyhow!("missing {}\n{}", name, USAGE))
+    };
+    let mut sweep = Sweep::new(axis("--alpha")?, axis("--pump")?);
+    if let Some(ticks) = common::value(&args, "--ticks", USAGE)? {
+        sweep.ticks = ticks;
+    }
+    if let Some(window) = common::value(&args, "--window", USAGE)? {
+        sweep.window = window;
+    }
+    if let Some(threads) = common::value(&args, "--threads", USAGE)? {
+        sweep.threads = threads;
+    }
+    let output: PathBuf =
+        common::value(&args, "--output", USAGE)?.unwrap_or_else(|| PathBuf::from("output"));
+
+    let map = sweep.run(&config)?;
+    std::fs::create_dir_all(&output)
+        .with_context(|| format!("creating output directory {}", output.display()))?;
+    map.save_csv(output.join("sweep.csv"))?;
+    map.render(output.join("sweep.png"))
+}
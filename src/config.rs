@@ -0,0 +1,422 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use lle::Freq;
+use serde::Deserialize;
+
+use crate::{
+    anyhow, check_mode, finite, non_negative, positive, Component, ComponentProperty, Coupling,
+    Drive, InitialCondition, LossProfile, ModeTable, NoiseModel, PumpEnvelope, PumpShape, Raman,
+    Result, Thermal, Topology, Worker, WorkerProperty, DEFAULT_MODES,
+};
+
+///declarative setup of a [`Worker`] loaded from a toml file,
+///every absent field keeps the default of [`WorkerProperty::new`]
+///
+///```toml
+///topology = "Coupled"
+///modes = 256
+///seed = 42
+///
+///[first]
+///alpha = 3.0
+///pump = 3.94
+///dispersion = [{ order = 3, value = 1e-4 }]
+///dint = "dint.csv"
+///
+///[second]
+///alpha = 3.0
+///
+///[[couplings]]
+///mode = 0
+///strength = 1.0
+///
+///[noise]
+///kind = "Vacuum"
+///photon = 1e-4
+///
+///[initial]
+///kind = "Soliton"
+///position = 3.14
+///```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub topology: Topology,
+    pub modes: usize,
+    ///seed of the random generator, a random one if absent
+    pub seed: Option<u64>,
+    pub record_step: Option<u32>,
+    pub simu_step: Option<f64>,
+    pub first: ComponentConfig,
+    ///only allowed for [`Topology::Coupled`]
+    pub second: ComponentConfig,
+    ///replaces the default coupling if present
+    pub couplings: Option<Vec<CouplingConfig>>,
+    pub xpm: Option<f64>,
+    pub noise: NoiseModel,
+    pub initial: InitialCondition,
+    ///directory the relative table paths are resolved against
+    #[serde(skip)]
+    base: PathBuf,
+}
+
+///parameters of one component, see [`ComponentProperty`] for their meaning
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ComponentConfig {
+    pub alpha: Option<f64>,
+    pub pump: Option<f64>,
+    pub pump_phase: Option<f64>,
+    pub pump_shape: Option<PumpShape>,
+    pub pump_envelope: Option<PumpEnvelope>,
+    pub drives: Vec<DriveConfig>,
+    pub drift: Option<f64>,
+    pub linear: Option<f64>,
+    pub dispersion: Vec<DispersionConfig>,
+    ///csv file of the measured `D_int`
+    pub dint: Option<PathBuf>,
+    pub loss: Option<f64>,
+    pub loss_profile: Option<LossProfile>,
+    ///csv file of the extra loss of each mode, exclusive with `loss_profile`
+    pub loss_table: Option<PathBuf>,
+    pub raman: Option<Raman>,
+    pub shock_time: Option<f64>,
+    pub thermal: Option<Thermal>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DispersionConfig {
+    pub order: u32,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DriveConfig {
+    pub mode: Freq,
+    pub amplitude: f64,
+    #[serde(default)]
+    pub offset: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CouplingConfig {
+    pub mode: Freq,
+    pub strength: f64,
+    #[serde(default)]
+    pub phase: f64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            topology: Topology::default(),
+            modes: DEFAULT_MODES,
+            seed: None,
+            record_step: None,
+            simu_step: None,
+            first: ComponentConfig::default(),
+            second: ComponentConfig::default(),
+            couplings: None,
+            xpm: None,
+            noise: NoiseModel::default(),
+            initial: InitialCondition::default(),
+            base: PathBuf::new(),
+        }
+    }
+}
+
+impl Config {
+    ///load the configuration at `path`, tables are resolved relative to its directory
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("reading config {}", path.display()))?;
+        let mut config =
+            Self::parse(&content).with_context(|| format!("parsing config {}", path.display()))?;
+        config.base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(config)
+    }
+    ///parse a configuration, tables are resolved relative to the working directory
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+    ///the simulated parameters, the seed is drawn randomly if not configured
+    pub fn property(&self) -> Result<WorkerProperty> {
+        let mut property = WorkerProperty::new(self.modes, self.seed.unwrap_or_else(rand::random));
+        if let Some(v) = self.record_step {
+            property.record_step = v;
+        }
+        if let Some(v) = self.simu_step {
+            property.simu_step = v;
+        }
+        if self.topology == Topology::Single {
+            if self.second != ComponentConfig::default() {
+                return Err(anyhow!("[second] is not allowed for a single resonator"));
+            }
+            if self.couplings.is_some() || self.xpm.is_some() {
+                return Err(anyhow!(
+                    "couplings and xpm are not allowed for a single resonator"
+                ));
+            }
+        }
+        for (c, config) in [
+            (Component::First, &self.first),
+            (Component::Second, &self.second),
+        ] {
+            let name = match c {
+                Component::First => "first",
+                Component::Second => "second",
+            };
+            config
+                .apply(&mut property.components[c.index()], self.modes, &self.base)
+                .with_context(|| format!("in [{}]", name))?;
+        }
+        if let Some(ref couplings) = self.couplings {
+            property.couplings.clear();
+            for c in couplings {
+                check_mode("coupling", c.mode, self.modes)?;
+                finite("coupling strength", c.strength)?;
+                finite("coupling phase", c.phase)?;
+                let coupling = Coupling {
+                    strength: c.strength,
+                    phase: c.phase,
+                };
+                if property.couplings.insert(c.mode, coupling).is_some() {
+                    return Err(anyhow!("coupling at mode {} set twice", c.mode));
+                }
+            }
+        }
+        if let Some(v) = self.xpm {
+            property.xpm = finite("xpm", v)?;
+        }
+        if let Some(v) = self.noise.amplitude() {
+            if !(v >= 0. && v.is_finite()) {
+                return Err(anyhow!("noise amplitude should be non-negative, got {}", v));
+            }
+        }
+        property.noise = self.noise;
+        property.check()?;
+        Ok(property)
+    }
+    ///build the worker and initialize its fields with the configured initial condition
+    pub fn build(&self) -> Result<Worker> {
+        let mut worker = Worker::from_property(self.topology, self.property()?)?;
+        worker
+            .reset(&self.initial)
+            .context("generating the initial condition")?;
+        Ok(worker)
+    }
+}
+
+impl ComponentConfig {
+    fn apply(&self, p: &mut ComponentProperty, modes: usize, base: &Path) -> Result<()> {
+        let set = |name: &str, dst: &mut f64, value: Option<f64>| -> Result<()> {
+            if let Some(v) = value {
+                *dst = finite(name, v)?;
+            }
+            Ok(())
+        };
+        set("alpha", &mut p.alpha, self.alpha)?;
+        set("pump", &mut p.pump, self.pump)?;
+        set("pump_phase", &mut p.pump_phase, self.pump_phase)?;
+        set("drift", &mut p.drift, self.drift)?;
+        set("linear", &mut p.linear, self.linear)?;
+        set("loss", &mut p.loss, self.loss)?;
+        set("shock_time", &mut p.shock_time, self.shock_time)?;
+        if p.loss < 0. {
+            return Err(anyhow!("loss should be non-negative, got {}", p.loss));
+        }
+        non_negative("shock_time", p.shock_time)?;
+        if let Some(shape) = self.pump_shape {
+            shape.check()?;
+            p.pump_shape = shape;
+        }
+        if let Some(envelope) = self.pump_envelope {
+            envelope.check()?;
+            p.pump_envelope = envelope;
+        }
+        for d in self.drives.iter() {
+            check_mode("drive", d.mode, modes)?;
+            let drive = Drive {
+                amplitude: finite("drive amplitude", d.amplitude)?,
+                offset: finite("drive offset", d.offset)?,
+            };
+            if p.drives.insert(d.mode, drive).is_some() {
+                return Err(anyhow!("drive at mode {} set twice", d.mode));
+            }
+        }
+        for d in self.dispersion.iter() {
+            if d.order <= 2 {
+                return Err(anyhow!(
+                    "dispersion order should be higher than 2, got {}, use `drift` and `linear` for lower orders",
+                    d.order
+                ));
+            }
+            if p.dispersion
+                .insert(d.order, finite("dispersion", d.value)?)
+                .is_some()
+            {
+                return Err(anyhow!("dispersion of order {} set twice", d.order));
+            }
+        }
+        if let Some(ref path) = self.dint {
+            p.dint = Some(ModeTable::from_csv(base.join(path))?);
+        }
+        match (&self.loss_profile, &self.loss_table) {
+            (Some(_), Some(_)) => {
+                return Err(anyhow!("loss_profile and loss_table are exclusive"));
+            }
+            (Some(profile), None) => p.loss_profile = profile.clone(),
+            (None, Some(path)) => {
+                p.loss_profile = LossProfile::Table(ModeTable::from_csv(base.join(path))?)
+            }
+            (None, None) => (),
+        }
+        p.loss_profile.check()?;
+        if let Some(raman) = self.raman {
+            if !(0. ..=1.).contains(&raman.fraction) {
+                return Err(anyhow!(
+                    "raman fraction should be in [0, 1], got {}",
+                    raman.fraction
+                ));
+            }
+            positive("raman response time", raman.time)?;
+            p.raman = Some(raman);
+        }
+        if let Some(thermal) = self.thermal {
+            positive("thermal relaxation time", thermal.time)?;
+            finite("thermal coefficient", thermal.coefficient)?;
+            p.thermal = Some(thermal);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///the toml example in the documentation of [`Config`]
+    fn doc_example() -> String {
+        include_str!("config.rs")
+            .lines()
+            .skip_while(|l| *l != "///```toml")
+            .skip(1)
+            .take_while(|l| *l != "///```")
+            .map(|l| l.trim_start_matches("///").to_string() + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn doc_example_builds() {
+        let dir = std::env::temp_dir().join(format!("lle_config_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dint: String = (-128..128)
+            .map(|m| format!("{},{}\n", m, 1e-3 * (m * m) as f64))
+            .collect();
+        std::fs::write(dir.join("dint.csv"), dint).unwrap();
+        std::fs::write(dir.join("config.toml"), doc_example()).unwrap();
+        let worker = Config::load(dir.join("config.toml")).and_then(|c| c.build());
+        std::fs::remove_dir_all(&dir).unwrap();
+        let worker = worker.unwrap();
+        assert_eq!(worker.topology(), Topology::Coupled);
+        assert_eq!(worker.get_property().seed, 42);
+        assert_eq!(worker.get_state()[0].len(), 256);
+    }
+
+    #[test]
+    fn validation_errors() {
+        let cases = [
+            (
+                "topology = \"Single\"\n[second]\nalpha = 1.0",
+                "[second] is not allowed",
+            ),
+            (
+                "topology = \"Single\"\nxpm = 1.0",
+                "couplings and xpm are not allowed",
+            ),
+            ("[first]\nalpha = nan", "alpha should be finite"),
+            ("[first]\nloss = -1.0", "loss should be non-negative"),
+            (
+                "[first]\nshock_time = -1e-3",
+                "shock_time should be non-negative",
+            ),
+            (
+                "[first]\npump_shape = { kind = \"PulseTrain\", width = 0.0, count = 1 }",
+                "pulse width should be positive",
+            ),
+            (
+                "[first]\npump_envelope = { kind = \"Ramp\", duration = -1.0 }",
+                "ramp duration should be positive",
+            ),
+            (
+                "[first]\ndrives = [{ mode = 1, amplitude = 1.0 }, { mode = 1, amplitude = 2.0 }]",
+                "drive at mode 1 set twice",
+            ),
+            (
+                "[first]\ndrives = [{ mode = 100, amplitude = 1.0 }]",
+                "drive at mode 100 out of the simulated modes -64..=63",
+            ),
+            (
+                "[first]\ndispersion = [{ order = 2, value = 1.0 }]",
+                "dispersion order should be higher than 2",
+            ),
+            (
+                "[first]\ndispersion = [{ order = 3, value = 1.0 }, { order = 3, value = 2.0 }]",
+                "dispersion of order 3 set twice",
+            ),
+            (
+                "[first]\nloss_profile = { kind = \"Flat\" }\nloss_table = \"loss.csv\"",
+                "loss_profile and loss_table are exclusive",
+            ),
+            (
+                "[first]\nloss_profile = { kind = \"RollOff\", curvature = -1e-3 }",
+                "loss curvature should be non-negative",
+            ),
+            (
+                "[first]\nloss_profile = { kind = \"Band\", center = 0.0, width = 0.0, depth = 1.0 }",
+                "loss band width should be positive",
+            ),
+            (
+                "[first]\nraman = { fraction = 1.5 }",
+                "raman fraction should be in [0, 1]",
+            ),
+            (
+                "[first]\nraman = { time = 0.0 }",
+                "raman response time should be positive",
+            ),
+            (
+                "[first]\nthermal = { time = -1.0, coefficient = 1.0 }",
+                "thermal relaxation time should be positive",
+            ),
+            (
+                "[[couplings]]\nmode = 0\nstrength = 1.0\n[[couplings]]\nmode = 0\nstrength = 2.0",
+                "coupling at mode 0 set twice",
+            ),
+            (
+                "[[couplings]]\nmode = 64\nstrength = 1.0",
+                "coupling at mode 64 out of the simulated modes",
+            ),
+            ("xpm = inf", "xpm should be finite"),
+            (
+                "[noise]\nkind = \"White\"\namplitude = -1.0",
+                "noise amplitude should be non-negative",
+            ),
+            ("modes = 0", "mode number should be positive"),
+            ("record_step = 0", "record step should be positive"),
+            ("simu_step = 0.0", "simulation step should be positive"),
+        ];
+        for (content, message) in cases {
+            let error = Config::parse(content)
+                .and_then(|c| c.property())
+                .expect_err(content);
+            let error = format!("{:#}", error);
+            assert!(error.contains(message), "{}: {}", content, error);
+        }
+        assert!(Config::parse("[first]\nalpah = 1.0").is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+
+use lle::{num_complex::Complex64, CoupleOp, Freq, NonLinearOp, Step};
+use serde::{Deserialize, Serialize};
+
+use crate::mode_range;
+
+///linear coupling between the same mode of the two components
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Coupling {
+    pub strength: f64,
+    pub phase: f64,
+}
+
+///mode splitting at arbitrary modes, each coupling mixes the two components as
+///`dA1/dt = i * g * exp(i * φ) * A2` and `dA2/dt = i * g * exp(-i * φ) * A1`
+#[derive(Debug, Clone, Default)]
+pub struct ModeCrossings {
+    couplings: Vec<(Freq, Coupling)>,
+}
+
+impl ModeCrossings {
+    pub fn new(couplings: &BTreeMap<Freq, Coupling>) -> Self {
+        Self {
+            couplings: couplings.iter().map(|(&m, &c)| (m, c)).collect(),
+        }
+    }
+}
+
+impl CoupleOp<f64> for ModeCrossings {
+    fn mix(&self, freq1: &mut [Complex64], freq2: &mut [Complex64], step_dist: f64) {
+        let len = freq1.len();
+        let (lower, upper) = mode_range(len);
+        for &(mode, Coupling { strength, phase }) in self.couplings.iter() {
+            //modes out of the simulated range are ignored
+            if mode < lower || mode > upper {
+                continue;
+            }
+            let index = mode.rem_euclid(len as Freq) as usize;
+            let (sin, cos) = (strength * step_dist).sin_cos();
+            let to1 = Complex64::i() * Complex64::from_polar(sin, phase);
+            let to2 = Complex64::i() * Complex64::from_polar(sin, -phase);
+            let (a1, a2) = (freq1[index], freq2[index]);
+            freq1[index] = a1 * cos + to1 * a2;
+            freq2[index] = a2 * cos + to2 * a1;
+        }
+    }
+}
+
+///cross phase modulation from the other component, `i * coefficient * |A|²`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrossPhaseMod {
+    pub coefficient: f64,
+}
+
+impl NonLinearOp<f64> for CrossPhaseMod {
+    fn get_value(&mut self, _step: Step, state: &[Complex64], dst: &mut [Complex64]) {
+        dst.iter_mut()
+            .zip(state.iter())
+            .for_each(|(d, x)| *d = Complex64::i() * self.coefficient * x.norm_sqr());
+    }
+    fn skip(&self) -> bool {
+        self.coefficient == 0.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+
+    fn crossing(mode: Freq, strength: f64, phase: f64) -> ModeCrossings {
+        ModeCrossings::new(&BTreeMap::from([(mode, Coupling { strength, phase })]))
+    }
+
+    fn spectrum(len: usize, seed: f64) -> Vec<Complex64> {
+        (0..len)
+            .map(|i| Complex64::new((seed * i as f64).sin(), (seed * i as f64 + 1.).cos()))
+            .collect()
+    }
+
+    #[test]
+    fn conserves_power() {
+        let len = 16;
+        let (mut freq1, mut freq2) = (spectrum(len, 0.7), spectrum(len, 1.3));
+        let (init1, init2) = (freq1.clone(), freq2.clone());
+        let op = crossing(-2, 1.7, 0.4);
+        for _ in 0..10 {
+            op.mix(&mut freq1, &mut freq2, 0.13);
+        }
+        let index = 14;
+        let power = |a: &[Complex64], b: &[Complex64]| a[index].norm_sqr() + b[index].norm_sqr();
+        assert!((power(&freq1, &freq2) - power(&init1, &init2)).abs() < 1e-12);
+        assert!((freq1[index] - init1[index]).norm() > 1e-3);
+        //the other modes are untouched
+        for i in (0..len).filter(|&i| i != index) {
+            assert_eq!((freq1[i], freq2[i]), (init1[i], init2[i]));
+        }
+    }
+
+    #[test]
+    fn full_transfer() {
+        let len = 8;
+        let (strength, phase) = (2., 0.3);
+        let op = crossing(3, strength, phase);
+        let a = Complex64::new(0.6, -0.8);
+        let mut freq1 = vec![Complex64::default(); len];
+        let mut freq2 = vec![Complex64::default(); len];
+        freq1[3] = a;
+        op.mix(&mut freq1, &mut freq2, PI / 2. / strength);
+        assert!(freq1[3].norm() < 1e-12);
+        assert!((freq2[3] - Complex64::i() * Complex64::from_polar(1., -phase) * a).norm() < 1e-12);
+        //a second quarter period brings it back with the sign flipped
+        op.mix(&mut freq1, &mut freq2, PI / 2. / strength);
+        assert!(freq2[3].norm() < 1e-12);
+        assert!((freq1[3] + a).norm() < 1e-12);
+    }
+
+    #[test]
+    fn out_of_range_ignored() {
+        let (mut freq1, mut freq2) = (spectrum(8, 0.7), spectrum(8, 1.3));
+        let (init1, init2) = (freq1.clone(), freq2.clone());
+        crossing(4, 1., 0.).mix(&mut freq1, &mut freq2, 0.5);
+        assert_eq!((freq1, freq2), (init1, init2));
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+
+use lle::{num_complex::Complex64, ConstOp, Freq, Step};
+use serde::{Deserialize, Serialize};
+
+use crate::mode_range;
+
+///an auxiliary laser driving a single mode
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Drive {
+    ///amplitude in the same normalization as the pump
+    pub amplitude: f64,
+    ///frequency offset of the laser from the mode, relative to the detuning of the main pump
+    pub offset: f64,
+}
+
+///frequency domain drive term of one component, each drive injecting
+///`amplitude * exp(-i * offset * t)` at its mode in the frame of the main pump
+#[derive(Debug, Clone, Default)]
+pub struct Drives {
+    drives: Vec<(Freq, Drive)>,
+    time: f64,
+}
+
+impl Drives {
+    pub fn new(drives: &BTreeMap<Freq, Drive>, time: f64) -> Self {
+        Self {
+            drives: drives.iter().map(|(&m, &d)| (m, d)).collect(),
+            time,
+        }
+    }
+    pub fn set_time(&mut self, time: f64) {
+        self.time = time;
+    }
+}
+
+impl ConstOp<f64> for Drives {
+    fn get_value(&self, _cur_step: Step, pos: usize, state: &[Complex64]) -> Complex64 {
+        let len = state.len();
+        let (_, upper) = mode_range(len);
+        let mode = if pos as Freq > upper {
+            pos as Freq - len as Freq
+        } else {
+            pos as Freq
+        };
+        //the spectrum is unnormalized, a cw field of amplitude `a` has `a * len` at its mode
+        self.drives
+            .iter()
+            .filter(|(m, _)| *m == mode)
+            .map(|(_, d)| Complex64::from_polar(d.amplitude * len as f64, -d.offset * self.time))
+            .sum()
+    }
+    fn skip(&self) -> bool {
+        self.drives.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+
+    #[test]
+    fn spectrum() {
+        let len = 16;
+        let state = vec![Complex64::default(); len];
+        let drives = BTreeMap::from([
+            (
+                -3,
+                Drive {
+                    amplitude: 0.5,
+                    offset: 2.,
+                },
+            ),
+            (
+                5,
+                Drive {
+                    amplitude: 1.5,
+                    offset: 0.,
+                },
+            ),
+        ]);
+        let time = 0.3;
+        let op = Drives::new(&drives, time);
+        for pos in 0..len {
+            let expected = match pos {
+                //negative modes wrap to the upper half of the fft bins
+                13 => Complex64::from_polar(0.5 * len as f64, -2. * time),
+                5 => Complex64::new(1.5 * len as f64, 0.),
+                _ => Complex64::default(),
+            };
+            assert!((op.get_value(0, pos, &state) - expected).norm() < 1e-12);
+        }
+        assert!(!op.skip());
+        assert!(Drives::new(&BTreeMap::new(), time).skip());
+    }
+
+    #[test]
+    fn phase_follows_time() {
+        let len = 8;
+        let state = vec![Complex64::default(); len];
+        let drives = BTreeMap::from([(
+            -4,
+            Drive {
+                amplitude: 1.,
+                offset: -0.5,
+            },
+        )]);
+        let mut op = Drives::new(&drives, 0.);
+        let pos = (-4 as Freq).rem_euclid(len as Freq) as usize;
+        assert!((op.get_value(0, pos, &state) - Complex64::new(8., 0.)).norm() < 1e-12);
+        //a quarter turn after `π / 2 / |offset|`
+        op.set_time(PI);
+        assert!((op.get_value(0, pos, &state) - Complex64::new(0., 8.)).norm() < 1e-12);
+    }
+}
@@ -19,6 +19,7 @@ fn u32_to_u8(arr: &mut [u32]) -> &mut [u8] {
 }
 
 pub struct DrawData {
+    data_len: usize,
     data: Vec<Vec<Complex64>>,
     plot_real: RawAnimator,
     plot_freq: RawAnimator,
@@ -138,6 +139,7 @@ impl DrawData {
     }
     pub fn new(data_len: usize, window_size: (usize, usize)) -> Self {
         DrawData {
+            data_len,
             data: Vec::default(),
             plot_real: RawAnimator::default(),
             plot_freq: {
@@ -241,7 +243,141 @@ impl DrawData {
             .inspect_err(|x| log::error!("{x}"))?;
         Ok(())
     }
+    pub fn data_len(&self) -> usize {
+        self.data_len
+    }
     pub fn push(&mut self, new_data: Vec<Complex64>) {
         self.data.push(new_data);
     }
 }
+
+///points of one series in a [`CurvePlot`]
+pub struct Curve {
+    pub label: String,
+    pub color: RGBColor,
+    pub points: Vec<(f64, f64)>,
+    ///joined by lines instead of drawn as separate points
+    pub line: bool,
+}
+
+///curves and the points marked on them
+pub type PlotData = (Vec<Curve>, Vec<(f64, f64)>);
+
+///a window plotting curves computed from the parameters, redrawn only when they change
+pub struct CurvePlot {
+    title: String,
+    x_desc: String,
+    y_desc: String,
+    window: Option<Window>,
+    size: (usize, usize),
+    buffer: Vec<u32>,
+}
+
+impl CurvePlot {
+    pub fn new(
+        title: impl Into<String>,
+        x_desc: impl Into<String>,
+        y_desc: impl Into<String>,
+        size: (usize, usize),
+    ) -> Self {
+        Self {
+            title: title.into(),
+            x_desc: x_desc.into(),
+            y_desc: y_desc.into(),
+            window: None,
+            size,
+            buffer: vec![0; size.0 * size.1],
+        }
+    }
+    ///whether the window is shown, `false` after the user closed it
+    pub fn is_open(&self) -> bool {
+        self.window.as_ref().is_none_or(Window::is_open)
+    }
+    ///draw `curves` as points with crosses at `markers`
+    pub fn draw(&mut self, curves: &[Curve], markers: &[(f64, f64)]) -> Result<()> {
+        let size = self.size;
+        {
+            let root =
+                BitMapBackend::<plotters_bitmap::bitmap_pixel::BGRXPixel>::with_buffer_and_format(
+                    u32_to_u8(&mut self.buffer),
+                    (size.0 as u32, size.1 as u32),
+                )?
+                .into_drawing_area();
+            root.fill(&WHITE)?;
+            let all = || curves.iter().flat_map(|c| c.points.iter()).chain(markers);
+            let (x_min, x_max) =
+                all().fold((f64::MAX, f64::MIN), |(l, h), p| (l.min(p.0), h.max(p.0)));
+            let (y_min, y_max) =
+                all().fold((f64::MAX, f64::MIN), |(l, h), p| (l.min(p.1), h.max(p.1)));
+            if x_min > x_max {
+                warn!("trying drawing empty curves");
+            } else {
+                //keep a margin and a non-empty range
+                let pad = |l: f64, h: f64| {
+                    let m = ((h - l) * 0.05).max(1e-3);
+                    l - m..h + m
+                };
+                let mut chart = ChartBuilder::on(&root)
+                    .margin(10)
+                    .x_label_area_size(35)
+                    .y_label_area_size(45)
+                    .build_cartesian_2d(pad(x_min, x_max), pad(y_min, y_max))?;
+                chart
+                    .configure_mesh()
+                    .x_desc(self.x_desc.as_str())
+                    .y_desc(self.y_desc.as_str())
+                    .draw()?;
+                for c in curves {
+                    let color = c.color;
+                    if c.line {
+                        chart
+                            .draw_series(LineSeries::new(
+                                c.points.iter().copied(),
+                                color.stroke_width(2),
+                            ))?
+                            .label(c.label.as_str())
+                            .legend(move |(x, y)| {
+                                PathElement::new([(x, y), (x + 10, y)], color.stroke_width(2))
+                            });
+                    } else {
+                        chart
+                            .draw_series(
+                                c.points
+                                    .iter()
+                                    .map(move |&p| Circle::new(p, 1, color.filled())),
+                            )?
+                            .label(c.label.as_str())
+                            .legend(move |(x, y)| Circle::new((x + 5, y), 3, color.filled()));
+                    }
+                }
+                chart.draw_series(
+                    markers
+                        .iter()
+                        .map(|&p| Cross::new(p, 6, BLACK.stroke_width(2))),
+                )?;
+                chart
+                    .configure_series_labels()
+                    .background_style(WHITE.mix(0.8))
+                    .border_style(BLACK)
+                    .draw()?;
+            }
+            root.present()?;
+        }
+        self.refresh()
+    }
+    ///show the last drawn buffer, keeping the window responsive
+    pub fn refresh(&mut self) -> Result<()> {
+        let size = self.size;
+        let window = match self.window {
+            Some(ref mut w) => w,
+            None => self.window.insert(
+                Window::new(&self.title, size.0, size.1, WindowOptions::default())
+                    .inspect_err(|x| log::error!("{x}"))?,
+            ),
+        };
+        window
+            .update_with_buffer(&self.buffer, size.0, size.1)
+            .inspect_err(|x| log::error!("{x}"))?;
+        Ok(())
+    }
+}
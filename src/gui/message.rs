@@ -3,7 +3,10 @@ use std::ops::RangeInclusive;
 use super::*;
 
 use iced::widget::{slider, text_input, Text};
-use lle_simulator::WorkerUpdate;
+use lle_simulator::{
+    anyhow, Component, InitialCondition, LossProfile, NoiseModel, PumpEnvelope, PumpShape, Result,
+    ScanProtocol, WorkerUpdate,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub enum SlideMessage {
@@ -18,6 +21,367 @@ pub enum Message {
     Slide((NewValue, SlideMessage)),
     Tick,
     Pause,
+    DispersionOrder(Component, String),
+    AddDispersion(Component),
+    RemoveDispersion(Component),
+    DintPath(Component, String),
+    LoadDint(Component),
+    ClearDint(Component),
+    LossProfileKind(Component, ProfileKind),
+    LossTablePath(Component, String),
+    LoadLossTable(Component),
+    CoupleMode(String),
+    AddCoupling,
+    RemoveCoupling,
+    XpmPreset(XpmPreset),
+    PumpShapeKind(Component, ShapeKind),
+    DriveMode(Component, String),
+    AddDrive(Component),
+    RemoveDrive(Component),
+    PumpEnvelopeKind(Component, EnvelopeKind),
+    ToggleRaman(Component, bool),
+    ToggleThermal(Component, bool),
+    NoiseKind(NoiseKind),
+    InitKind(InitKind),
+    InitPositions(String),
+    Reset,
+    ToggleBistability(bool),
+    ///steady states sampled in the background, `None` if the sampling failed
+    SCurveSampled(Option<SCurve>),
+    ToggleMiGain(bool),
+    SeedInput(String),
+    SnapshotPath(String),
+    SaveSnapshot,
+    LoadSnapshot,
+    Restart,
+    ScanInput(ScanField, String),
+    StartScan,
+    StopScan,
+    ScanRecordPath(String),
+    SaveScanRecord,
+}
+
+///text fields of the detuning scan panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanField {
+    Start,
+    Stop,
+    Rate,
+    Back,
+    BackRate,
+}
+
+impl ScanField {
+    pub const ALL: [ScanField; 5] = [
+        ScanField::Start,
+        ScanField::Stop,
+        ScanField::Rate,
+        ScanField::Back,
+        ScanField::BackRate,
+    ];
+    pub fn index(self) -> usize {
+        self as usize
+    }
+    pub fn placeholder(self) -> &'static str {
+        match self {
+            ScanField::Start => "start",
+            ScanField::Stop => "stop",
+            ScanField::Rate => "rate",
+            ScanField::Back => "back to (optional)",
+            ScanField::BackRate => "back rate",
+        }
+    }
+}
+
+///inputs of the detuning scan panel, a backward tuning is added if `Back` is filled
+#[derive(Debug, Clone, Default)]
+pub struct ScanForm {
+    pub fields: [String; 5],
+}
+
+impl ScanForm {
+    fn parse(&self, field: ScanField) -> Result<f64> {
+        let s = self.fields[field.index()].trim();
+        s.parse()
+            .map_err(|_| anyhow!("illegal scan {} '{}'", field.placeholder(), s))
+    }
+    pub fn protocol(&self) -> Result<ScanProtocol> {
+        let (start, stop, rate) = (
+            self.parse(ScanField::Start)?,
+            self.parse(ScanField::Stop)?,
+            self.parse(ScanField::Rate)?,
+        );
+        if self.fields[ScanField::Back.index()].trim().is_empty() {
+            return Ok(ScanProtocol::linear(start, stop, rate));
+        }
+        let back_rate = if self.fields[ScanField::BackRate.index()].trim().is_empty() {
+            rate
+        } else {
+            self.parse(ScanField::BackRate)?
+        };
+        Ok(ScanProtocol::backward(
+            start,
+            stop,
+            rate,
+            self.parse(ScanField::Back)?,
+            back_rate,
+        ))
+    }
+}
+
+///cross phase modulation coefficients of common physical configurations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XpmPreset {
+    CounterPropagating,
+    OrthogonalPolarization,
+    Disabled,
+}
+
+impl XpmPreset {
+    pub const ALL: [XpmPreset; 3] = [
+        XpmPreset::CounterPropagating,
+        XpmPreset::OrthogonalPolarization,
+        XpmPreset::Disabled,
+    ];
+    pub fn coefficient(self) -> f64 {
+        match self {
+            XpmPreset::CounterPropagating => 2.,
+            XpmPreset::OrthogonalPolarization => 2. / 3.,
+            XpmPreset::Disabled => 0.,
+        }
+    }
+    pub fn of(coefficient: f64) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|x| x.coefficient() == coefficient)
+    }
+}
+
+impl std::fmt::Display for XpmPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            XpmPreset::CounterPropagating => "Counter-propagating (2)",
+            XpmPreset::OrthogonalPolarization => "Orthogonal Polarization (2/3)",
+            XpmPreset::Disabled => "Disabled (0)",
+        })
+    }
+}
+
+///initial conditions selectable from the panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitKind {
+    Noise,
+    CwSteady,
+    Soliton,
+    Solitons,
+}
+
+impl InitKind {
+    pub const ALL: [InitKind; 4] = [
+        InitKind::Noise,
+        InitKind::CwSteady,
+        InitKind::Soliton,
+        InitKind::Solitons,
+    ];
+    ///build the initial condition from `positions`, the comma separated position of the first
+    ///soliton and the spacings of the following ones
+    pub fn initial_condition(self, positions: &str) -> Result<InitialCondition> {
+        let mut values = positions
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                s.parse::<f64>()
+                    .map_err(|_| anyhow!("illegal soliton position '{}'", s))
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter();
+        let position = values.next().unwrap_or(std::f64::consts::PI);
+        Ok(match self {
+            InitKind::Noise => InitialCondition::Noise,
+            InitKind::CwSteady => InitialCondition::CwSteady,
+            InitKind::Soliton => InitialCondition::Soliton { position },
+            InitKind::Solitons => InitialCondition::Solitons {
+                position,
+                spacings: values.collect(),
+            },
+        })
+    }
+}
+
+impl std::fmt::Display for InitKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            InitKind::Noise => "Noise",
+            InitKind::CwSteady => "CW Steady State",
+            InitKind::Soliton => "Soliton",
+            InitKind::Solitons => "Multiple Solitons",
+        })
+    }
+}
+
+///noise models selectable from the panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseKind {
+    None,
+    Vacuum,
+    White,
+}
+
+impl NoiseKind {
+    pub const ALL: [NoiseKind; 3] = [NoiseKind::None, NoiseKind::Vacuum, NoiseKind::White];
+    pub fn of(noise: &NoiseModel) -> Self {
+        match noise {
+            NoiseModel::None => NoiseKind::None,
+            NoiseModel::Vacuum { .. } => NoiseKind::Vacuum,
+            NoiseModel::White { .. } => NoiseKind::White,
+        }
+    }
+    pub fn default_model(self) -> NoiseModel {
+        match self {
+            NoiseKind::None => NoiseModel::None,
+            NoiseKind::Vacuum => NoiseModel::Vacuum { photon: 1e-4 },
+            NoiseKind::White => NoiseModel::White { amplitude: 1e-4 },
+        }
+    }
+}
+
+impl std::fmt::Display for NoiseKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            NoiseKind::None => "None",
+            NoiseKind::Vacuum => "Vacuum (one photon per mode)",
+            NoiseKind::White => "White",
+        })
+    }
+}
+
+///fast time pump profiles selectable from the panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShapeKind {
+    Cw,
+    PulseTrain,
+    Modulated,
+}
+
+impl ShapeKind {
+    pub const ALL: [ShapeKind; 3] = [ShapeKind::Cw, ShapeKind::PulseTrain, ShapeKind::Modulated];
+    pub fn of(shape: &PumpShape) -> Self {
+        match shape {
+            PumpShape::Cw => ShapeKind::Cw,
+            PumpShape::PulseTrain { .. } => ShapeKind::PulseTrain,
+            PumpShape::Modulated { .. } => ShapeKind::Modulated,
+        }
+    }
+    pub fn default_shape(self) -> PumpShape {
+        match self {
+            ShapeKind::Cw => PumpShape::Cw,
+            ShapeKind::PulseTrain => PumpShape::PulseTrain {
+                width: 0.1,
+                count: 1,
+            },
+            ShapeKind::Modulated => PumpShape::Modulated {
+                amplitude_depth: 0.,
+                phase_depth: 1.,
+                harmonic: 1,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for ShapeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ShapeKind::Cw => "CW",
+            ShapeKind::PulseTrain => "Pulse Train",
+            ShapeKind::Modulated => "Modulated",
+        })
+    }
+}
+
+///slow time pump envelopes selectable from the panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvelopeKind {
+    Constant,
+    Ramp,
+    Sine,
+}
+
+impl EnvelopeKind {
+    pub const ALL: [EnvelopeKind; 3] = [
+        EnvelopeKind::Constant,
+        EnvelopeKind::Ramp,
+        EnvelopeKind::Sine,
+    ];
+    pub fn of(envelope: &PumpEnvelope) -> Self {
+        match envelope {
+            PumpEnvelope::Constant => EnvelopeKind::Constant,
+            PumpEnvelope::Ramp { .. } => EnvelopeKind::Ramp,
+            PumpEnvelope::Sine { .. } => EnvelopeKind::Sine,
+        }
+    }
+    pub fn default_envelope(self) -> PumpEnvelope {
+        match self {
+            EnvelopeKind::Constant => PumpEnvelope::Constant,
+            EnvelopeKind::Ramp => PumpEnvelope::Ramp { duration: 10. },
+            EnvelopeKind::Sine => PumpEnvelope::Sine {
+                depth: 0.1,
+                period: 10.,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for EnvelopeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EnvelopeKind::Constant => "Constant",
+            EnvelopeKind::Ramp => "Ramp",
+            EnvelopeKind::Sine => "Sine",
+        })
+    }
+}
+
+///analytical loss profiles selectable from the panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileKind {
+    Flat,
+    RollOff,
+    Band,
+}
+
+impl ProfileKind {
+    pub const ALL: [ProfileKind; 3] = [ProfileKind::Flat, ProfileKind::RollOff, ProfileKind::Band];
+    pub fn of(profile: &LossProfile) -> Option<Self> {
+        match profile {
+            LossProfile::Flat => Some(ProfileKind::Flat),
+            LossProfile::RollOff { .. } => Some(ProfileKind::RollOff),
+            LossProfile::Band { .. } => Some(ProfileKind::Band),
+            LossProfile::Table(_) => None,
+        }
+    }
+    pub fn default_profile(self) -> LossProfile {
+        match self {
+            ProfileKind::Flat => LossProfile::Flat,
+            ProfileKind::RollOff => LossProfile::RollOff { curvature: 1e-4 },
+            ProfileKind::Band => LossProfile::Band {
+                center: 0.,
+                width: 10.,
+                depth: 1.,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for ProfileKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ProfileKind::Flat => "Flat",
+            ProfileKind::RollOff => "Roll-off",
+            ProfileKind::Band => "Absorption Band",
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -40,7 +404,7 @@ impl NewValue {
 
 #[derive(Clone, Debug)]
 pub struct Control<T = f64> {
-    call_back: fn(T) -> WorkerUpdate,
+    property: WorkerUpdate,
     desc: String,
     range: Option<Range<T>>,
 }
@@ -63,24 +427,24 @@ impl Range<f64> {
 }
 
 impl Control<f64> {
-    pub fn new(
-        call_back: fn(f64) -> WorkerUpdate,
-        desc: impl Into<String>,
-        center: Option<f64>,
-    ) -> Self {
+    pub fn new(property: WorkerUpdate, desc: impl Into<String>, center: Option<f64>) -> Self {
         Self {
-            call_back,
+            property,
             desc: desc.into(),
             range: center.map(Range::from_center),
         }
     }
+    pub fn property(&self) -> WorkerUpdate {
+        self.property
+    }
     pub fn range_mut(&mut self) -> Option<&mut Range<f64>> {
         self.range.as_mut()
     }
     pub fn view(&self, value: WorkerUpdate) -> Element<Message> {
         const INPUT_WIDTH_PORTION: u16 = 8;
         let v = property_value_to_string(value);
-        let call_back = self.call_back;
+        let property = self.property;
+        let call_back = move |x| replace_property_value(property, x);
         let desc = Row::new()
             .spacing(5)
             .align_y(Alignment::Start)
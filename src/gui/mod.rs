@@ -1,62 +1,371 @@
 use super::*;
 
-use lle_simulator::{WorkerProperty, WorkerUpdate};
+use std::sync::Arc;
+
+use lle_simulator::{
+    cw_states, mi_gain, roll_number, s_curve, Component, ComponentProperty, CwState, Topology,
+    WorkerProperty, WorkerUpdate,
+};
 #[allow(unused)]
 use log::{debug, error, info, log_enabled, warn, Level};
+use plotters::style::{RGBColor, BLACK, BLUE, GREEN, MAGENTA, RED};
 
 mod chart;
 mod message;
 pub use chart::*;
 pub use message::*;
 
-fn from_property(p: &WorkerProperty, idx: usize) -> WorkerUpdate {
-    match idx {
-        0 => WorkerUpdate::Alpha(p.alpha),
-        1 => WorkerUpdate::Pump(p.pump),
-        2 => WorkerUpdate::Linear(p.linear),
-        3 => WorkerUpdate::RecordStep(p.record_step),
-        4 => WorkerUpdate::SimuStep(p.simu_step),
-        5 => WorkerUpdate::Couple(p.couple),
-        _ => unreachable!(),
+fn from_component_property(p: &ComponentProperty, c: Component) -> Vec<WorkerUpdate> {
+    let mut list = vec![
+        WorkerUpdate::Alpha(c, p.alpha),
+        WorkerUpdate::Pump(c, p.pump),
+        WorkerUpdate::PumpPhase(c, p.pump_phase),
+    ];
+    list.extend(
+        p.pump_shape
+            .parameters()
+            .into_iter()
+            .chain(p.pump_envelope.parameters())
+            .map(|(k, v)| WorkerUpdate::PumpParameter(c, k, v)),
+    );
+    list.extend(p.drives.iter().flat_map(|(&m, d)| {
+        [
+            WorkerUpdate::DriveAmplitude(c, m, d.amplitude),
+            WorkerUpdate::DriveOffset(c, m, d.offset),
+        ]
+    }));
+    list.extend([
+        WorkerUpdate::Drift(c, p.drift),
+        WorkerUpdate::Linear(c, p.linear),
+    ]);
+    list.extend(
+        p.dispersion
+            .iter()
+            .map(|(&order, &v)| WorkerUpdate::Dispersion(c, order, v)),
+    );
+    list.push(WorkerUpdate::Loss(c, p.loss));
+    list.extend(
+        p.loss_profile
+            .parameters()
+            .into_iter()
+            .map(|(k, v)| WorkerUpdate::LossParameter(c, k, v)),
+    );
+    if let Some(r) = p.raman {
+        list.push(WorkerUpdate::RamanFraction(c, r.fraction));
+        list.push(WorkerUpdate::RamanTime(c, r.time));
+    }
+    list.push(WorkerUpdate::ShockTime(c, p.shock_time));
+    if let Some(t) = p.thermal {
+        list.push(WorkerUpdate::ThermalTime(c, t.time));
+        list.push(WorkerUpdate::ThermalCoefficient(c, t.coefficient));
     }
+    list
 }
 
-pub fn from_property_array(p: WorkerProperty) -> [WorkerUpdate; 6] {
-    let mut a = [WorkerUpdate::Alpha(0.); 6];
-    (0..6).into_iter().for_each(|x| a[x] = from_property(&p, x));
-    a
+pub fn from_property_list(p: &WorkerProperty) -> Vec<WorkerUpdate> {
+    let mut list = Component::ALL
+        .iter()
+        .flat_map(|&c| from_component_property(&p.components[c.index()], c))
+        .collect::<Vec<_>>();
+    list.extend([
+        WorkerUpdate::RecordStep(p.record_step),
+        WorkerUpdate::SimuStep(p.simu_step),
+    ]);
+    list.extend(p.couplings.iter().flat_map(|(&m, c)| {
+        [
+            WorkerUpdate::Couple(m, c.strength),
+            WorkerUpdate::CouplePhase(m, c.phase),
+        ]
+    }));
+    list.push(WorkerUpdate::Xpm(p.xpm));
+    list.push(WorkerUpdate::Modes(p.modes));
+    if let Some(v) = p.noise.amplitude() {
+        list.push(WorkerUpdate::Noise(v));
+    }
+    list
 }
 
 pub fn property_value_to_string(v: WorkerUpdate) -> String {
     match v {
-        WorkerUpdate::Alpha(v)
-        | WorkerUpdate::Pump(v)
-        | WorkerUpdate::Linear(v)
+        WorkerUpdate::Alpha(_, v)
+        | WorkerUpdate::Pump(_, v)
+        | WorkerUpdate::PumpPhase(_, v)
+        | WorkerUpdate::PumpParameter(_, _, v)
+        | WorkerUpdate::DriveAmplitude(_, _, v)
+        | WorkerUpdate::DriveOffset(_, _, v)
+        | WorkerUpdate::Drift(_, v)
+        | WorkerUpdate::Linear(_, v)
+        | WorkerUpdate::Dispersion(_, _, v)
+        | WorkerUpdate::Loss(_, v)
+        | WorkerUpdate::LossParameter(_, _, v)
+        | WorkerUpdate::RamanFraction(_, v)
+        | WorkerUpdate::RamanTime(_, v)
+        | WorkerUpdate::ShockTime(_, v)
+        | WorkerUpdate::ThermalTime(_, v)
+        | WorkerUpdate::ThermalCoefficient(_, v)
         | WorkerUpdate::SimuStep(v)
-        | WorkerUpdate::Couple(v) => format!("{:.3E}", v),
+        | WorkerUpdate::Couple(_, v)
+        | WorkerUpdate::CouplePhase(_, v)
+        | WorkerUpdate::Xpm(v)
+        | WorkerUpdate::Noise(v) => format!("{:.3E}", v),
         WorkerUpdate::RecordStep(v) => v.to_string(),
+        WorkerUpdate::Modes(v) => v.to_string(),
+    }
+}
+
+///whether `a` and `b` update the same property, regardless of the value they carry
+pub fn same_property(a: WorkerUpdate, b: WorkerUpdate) -> bool {
+    match (a, b) {
+        (WorkerUpdate::Dispersion(c1, o1, _), WorkerUpdate::Dispersion(c2, o2, _)) => {
+            c1 == c2 && o1 == o2
+        }
+        (WorkerUpdate::LossParameter(c1, k1, _), WorkerUpdate::LossParameter(c2, k2, _)) => {
+            c1 == c2 && k1 == k2
+        }
+        (WorkerUpdate::PumpParameter(c1, k1, _), WorkerUpdate::PumpParameter(c2, k2, _)) => {
+            c1 == c2 && k1 == k2
+        }
+        (WorkerUpdate::DriveAmplitude(c1, m1, _), WorkerUpdate::DriveAmplitude(c2, m2, _))
+        | (WorkerUpdate::DriveOffset(c1, m1, _), WorkerUpdate::DriveOffset(c2, m2, _)) => {
+            c1 == c2 && m1 == m2
+        }
+        (WorkerUpdate::Couple(m1, _), WorkerUpdate::Couple(m2, _))
+        | (WorkerUpdate::CouplePhase(m1, _), WorkerUpdate::CouplePhase(m2, _)) => m1 == m2,
+        _ => {
+            std::mem::discriminant(&a) == std::mem::discriminant(&b)
+                && property_component(a) == property_component(b)
+        }
     }
 }
 
-pub fn map_property_to_idx(p: WorkerUpdate) -> usize {
+///the resonator a property belongs to, `None` for properties shared by the whole system
+pub fn property_component(p: WorkerUpdate) -> Option<Component> {
     match p {
-        WorkerUpdate::Alpha(_) => 0,
-        WorkerUpdate::Pump(_) => 1,
-        WorkerUpdate::Linear(_) => 2,
-        WorkerUpdate::RecordStep(_) => 3,
-        WorkerUpdate::SimuStep(_) => 4,
-        WorkerUpdate::Couple(_) => 5,
+        WorkerUpdate::Alpha(c, _)
+        | WorkerUpdate::Pump(c, _)
+        | WorkerUpdate::PumpPhase(c, _)
+        | WorkerUpdate::PumpParameter(c, _, _)
+        | WorkerUpdate::DriveAmplitude(c, _, _)
+        | WorkerUpdate::DriveOffset(c, _, _)
+        | WorkerUpdate::Drift(c, _)
+        | WorkerUpdate::Linear(c, _)
+        | WorkerUpdate::Dispersion(c, _, _)
+        | WorkerUpdate::Loss(c, _)
+        | WorkerUpdate::LossParameter(c, _, _)
+        | WorkerUpdate::RamanFraction(c, _)
+        | WorkerUpdate::RamanTime(c, _)
+        | WorkerUpdate::ShockTime(c, _)
+        | WorkerUpdate::ThermalTime(c, _)
+        | WorkerUpdate::ThermalCoefficient(c, _) => Some(c),
+        WorkerUpdate::RecordStep(_)
+        | WorkerUpdate::SimuStep(_)
+        | WorkerUpdate::Couple(_, _)
+        | WorkerUpdate::CouplePhase(_, _)
+        | WorkerUpdate::Xpm(_)
+        | WorkerUpdate::Modes(_)
+        | WorkerUpdate::Noise(_) => None,
     }
 }
 
 //only used from updating lower & higher bound of slider
 pub fn extract_property_value(p: WorkerUpdate) -> f64 {
     match p {
-        WorkerUpdate::Alpha(v) => v,
-        WorkerUpdate::Pump(v) => v,
-        WorkerUpdate::Linear(v) => v,
+        WorkerUpdate::Alpha(_, v) => v,
+        WorkerUpdate::Pump(_, v) => v,
+        WorkerUpdate::PumpPhase(_, v) => v,
+        WorkerUpdate::PumpParameter(_, _, v) => v,
+        WorkerUpdate::DriveAmplitude(_, _, v) => v,
+        WorkerUpdate::DriveOffset(_, _, v) => v,
+        WorkerUpdate::Drift(_, v) => v,
+        WorkerUpdate::Linear(_, v) => v,
+        WorkerUpdate::Dispersion(_, _, v) => v,
+        WorkerUpdate::Loss(_, v) => v,
+        WorkerUpdate::LossParameter(_, _, v) => v,
+        WorkerUpdate::RamanFraction(_, v) => v,
+        WorkerUpdate::RamanTime(_, v) => v,
+        WorkerUpdate::ShockTime(_, v) => v,
+        WorkerUpdate::ThermalTime(_, v) => v,
+        WorkerUpdate::ThermalCoefficient(_, v) => v,
         WorkerUpdate::RecordStep(_) => unreachable!(),
         WorkerUpdate::SimuStep(_) => unreachable!(),
-        WorkerUpdate::Couple(v) => v,
+        WorkerUpdate::Couple(_, v) => v,
+        WorkerUpdate::CouplePhase(_, v) => v,
+        WorkerUpdate::Xpm(v) => v,
+        WorkerUpdate::Modes(_) => unreachable!(),
+        WorkerUpdate::Noise(v) => v,
+    }
+}
+
+//build the update carrying a new value input by user
+pub fn replace_property_value(p: WorkerUpdate, v: f64) -> WorkerUpdate {
+    match p {
+        WorkerUpdate::Alpha(c, _) => WorkerUpdate::Alpha(c, v),
+        WorkerUpdate::Pump(c, _) => WorkerUpdate::Pump(c, v),
+        WorkerUpdate::PumpPhase(c, _) => WorkerUpdate::PumpPhase(c, v),
+        WorkerUpdate::PumpParameter(c, k, _) => WorkerUpdate::PumpParameter(c, k, v),
+        WorkerUpdate::DriveAmplitude(c, m, _) => WorkerUpdate::DriveAmplitude(c, m, v),
+        WorkerUpdate::DriveOffset(c, m, _) => WorkerUpdate::DriveOffset(c, m, v),
+        WorkerUpdate::Drift(c, _) => WorkerUpdate::Drift(c, v),
+        WorkerUpdate::Linear(c, _) => WorkerUpdate::Linear(c, v),
+        WorkerUpdate::Dispersion(c, order, _) => WorkerUpdate::Dispersion(c, order, v),
+        WorkerUpdate::Loss(c, _) => WorkerUpdate::Loss(c, v),
+        WorkerUpdate::LossParameter(c, k, _) => WorkerUpdate::LossParameter(c, k, v),
+        WorkerUpdate::RamanFraction(c, _) => WorkerUpdate::RamanFraction(c, v),
+        WorkerUpdate::RamanTime(c, _) => WorkerUpdate::RamanTime(c, v),
+        WorkerUpdate::ShockTime(c, _) => WorkerUpdate::ShockTime(c, v),
+        WorkerUpdate::ThermalTime(c, _) => WorkerUpdate::ThermalTime(c, v),
+        WorkerUpdate::ThermalCoefficient(c, _) => WorkerUpdate::ThermalCoefficient(c, v),
+        WorkerUpdate::RecordStep(_) => WorkerUpdate::RecordStep(v as u32),
+        WorkerUpdate::SimuStep(_) => WorkerUpdate::SimuStep(v),
+        WorkerUpdate::Couple(m, _) => WorkerUpdate::Couple(m, v),
+        WorkerUpdate::CouplePhase(m, _) => WorkerUpdate::CouplePhase(m, v),
+        WorkerUpdate::Xpm(_) => WorkerUpdate::Xpm(v),
+        WorkerUpdate::Modes(_) => WorkerUpdate::Modes(v as usize),
+        WorkerUpdate::Noise(_) => WorkerUpdate::Noise(v),
+    }
+}
+
+///parameters the bistability curve depends on, all but the detuning of the first component,
+///with the sampled detuning range
+#[derive(Debug, Clone, PartialEq)]
+pub struct SCurveKey {
+    topology: Topology,
+    ///the detuning of the first component set to 0, the others keep their offset to it
+    property: WorkerProperty,
+    range: (f64, f64),
+}
+
+impl SCurveKey {
+    pub fn new(topology: Topology, property: &WorkerProperty) -> Self {
+        let mut normalized = property.clone();
+        let alpha = property.components[0].alpha;
+        normalized.components.iter_mut().for_each(|c| {
+            //rounded against the drift of the offsets shifted together while scanning
+            c.alpha = ((c.alpha - alpha) * 1e9).round() / 1e9
+        });
+        let components = &property.components[..match topology {
+            Topology::Single => 1,
+            Topology::Coupled => 2,
+        }];
+        //the upper branch ends near the detuning of the peak power `F² / loss²`
+        let peak = components
+            .iter()
+            .filter(|c| c.loss > 0.)
+            .map(|c| (c.pump / c.loss).powi(2))
+            .fold(0., f64::max);
+        //widened in steps to keep the current detuning inside without resampling on every change
+        const STEP: f64 = 20.;
+        let (mut lower, mut upper) = (-10., 1.1 * peak + 10.);
+        if alpha - 5. < lower {
+            lower = ((alpha - 5.) / STEP).floor() * STEP;
+        }
+        if alpha + 5. > upper {
+            upper = ((alpha + 5.) / STEP).ceil() * STEP;
+        }
+        Self {
+            topology,
+            property: normalized,
+            range: (lower, upper),
+        }
+    }
+}
+
+///steady states sampled over the detuning of the first component, the expensive part of
+///[`bistability_curves`] to be computed off the ui thread
+#[derive(Clone)]
+pub struct SCurve {
+    key: Arc<SCurveKey>,
+    states: Arc<Vec<(f64, CwState)>>,
+}
+
+impl std::fmt::Debug for SCurve {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SCurve")
+            .field("range", &self.key.range)
+            .field("samples", &self.states.len())
+            .finish()
+    }
+}
+
+impl SCurve {
+    pub fn compute(key: SCurveKey) -> Self {
+        const SAMPLES: usize = 400;
+        let (lower, upper) = key.range;
+        let states = s_curve(
+            key.topology,
+            &key.property,
+            (0..SAMPLES).map(|i| lower + (upper - lower) * i as f64 / (SAMPLES - 1) as f64),
+        );
+        Self {
+            key: Arc::new(key),
+            states: Arc::new(states),
+        }
+    }
+    pub fn key(&self) -> &SCurveKey {
+        &self.key
+    }
+}
+
+///steady power of the first component over the detuning around the bistability from `curve`,
+///with markers at the steady states of the current detuning
+pub fn bistability_curves(
+    curve: &SCurve,
+    topology: Topology,
+    property: &WorkerProperty,
+) -> PlotData {
+    let alpha = property.components[0].alpha;
+    let (mut stable, mut unstable) = (Vec::new(), Vec::new());
+    for &(a, ref s) in curve.states.iter() {
+        if s.is_stable() {
+            stable.push((a, s.powers()[0]));
+        } else {
+            unstable.push((a, s.powers()[0]));
+        }
+    }
+    let markers = cw_states(topology, property)
+        .into_iter()
+        .map(|s| (alpha, s.powers()[0]))
+        .collect();
+    (
+        vec![
+            Curve {
+                label: "stable".into(),
+                color: BLUE,
+                points: stable,
+                line: false,
+            },
+            Curve {
+                label: "unstable".into(),
+                color: RED,
+                points: unstable,
+                line: false,
+            },
+        ],
+        markers,
+    )
+}
+
+///modulation instability gain over the modes of each homogeneous steady state,
+///with markers at the fastest growing sideband predicting the number of rolls
+pub fn mi_curves(topology: Topology, property: &WorkerProperty) -> PlotData {
+    const COLORS: [RGBColor; 5] = [BLUE, RED, GREEN, MAGENTA, BLACK];
+    let mut curves = Vec::new();
+    let mut markers = Vec::new();
+    for (i, s) in cw_states(topology, property).into_iter().enumerate() {
+        let gain = mi_gain(property, &s);
+        let peak = roll_number(&gain);
+        if let Some((m, g)) = peak {
+            markers.push((m as f64, g));
+        }
+        curves.push(Curve {
+            label: match peak {
+                Some((m, _)) => format!("Power {:.3}, {} Rolls", s.powers()[0], m),
+                None => format!("Power {:.3}, Stable", s.powers()[0]),
+            },
+            color: COLORS[i % COLORS.len()],
+            points: gain.into_iter().map(|(m, g)| (m as f64, g)).collect(),
+            line: true,
+        });
     }
+    (curves, markers)
 }
@@ -0,0 +1,155 @@
+use std::f64::consts::PI;
+
+use lle::num_complex::Complex64;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{anyhow, random_state, steady, ComponentProperty, Result, Topology, WorkerProperty};
+
+///initial field of a component
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum InitialCondition {
+    ///random noise, as a newly created worker
+    #[default]
+    Noise,
+    ///the lower homogeneous steady state
+    CwSteady,
+    ///a sech soliton on the cw background, centered at `position` in the fast time `[0, 2π)`
+    Soliton { position: f64 },
+    ///solitons at `position` and each following `spacings` apart
+    Solitons { position: f64, spacings: Vec<f64> },
+}
+
+impl InitialCondition {
+    ///positions of the solitons in the fast time
+    pub fn positions(&self) -> Vec<f64> {
+        match self {
+            InitialCondition::Noise | InitialCondition::CwSteady => Vec::new(),
+            InitialCondition::Soliton { position } => vec![*position],
+            InitialCondition::Solitons { position, spacings } => std::iter::once(*position)
+                .chain(spacings.iter().scan(*position, |p, s| {
+                    *p += s;
+                    Some(*p)
+                }))
+                .collect(),
+        }
+    }
+    ///fields of the components simulated by `topology` with `property`,
+    ///the cw background is the lower homogeneous steady state of all the components together
+    pub fn generate(
+        &self,
+        topology: Topology,
+        property: &WorkerProperty,
+        rng: &mut impl Rng,
+    ) -> Result<Vec<Vec<Complex64>>> {
+        if let InitialCondition::Noise = self {
+            let count = match topology {
+                Topology::Single => 1,
+                Topology::Coupled => 2,
+            };
+            return Ok((0..count)
+                .map(|_| random_state(property.modes, rng))
+                .collect());
+        }
+        let background = steady::cw_states(topology, property)
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("no homogeneous steady state to start from"))?;
+        background
+            .fields
+            .into_iter()
+            .zip(property.components.iter())
+            .map(|(b, p)| self.add_solitons(p, b, property.modes))
+            .collect()
+    }
+    ///field of a component with `property` on the cw `background` sampled on `modes` points
+    fn add_solitons(
+        &self,
+        property: &ComponentProperty,
+        background: Complex64,
+        modes: usize,
+    ) -> Result<Vec<Complex64>> {
+        let mut state = vec![background; modes];
+        let positions = self.positions();
+        if positions.is_empty() {
+            return Ok(state);
+        }
+        //stationary solution of `-iα * ψ + iD * ψ'' + i|ψ|² * ψ = 0` with `D = -linear / 2`
+        let dispersion = -property.linear / 2.;
+        if property.alpha <= 0. || dispersion <= 0. {
+            return Err(anyhow!(
+                "bright solitons need alpha > 0 and linear < 0, got alpha {} and linear {}",
+                property.alpha,
+                property.linear
+            ));
+        }
+        let amplitude = (2. * property.alpha).sqrt();
+        let inverse_width = (property.alpha / dispersion).sqrt();
+        //phase locked to the pump, the approximation of the perturbed soliton
+        let phase = property.pump_phase
+            + ((8. * property.alpha).sqrt() / (PI * property.pump))
+                .clamp(-1., 1.)
+                .acos();
+        let soliton = Complex64::from_polar(amplitude, phase);
+        for (i, x) in state.iter_mut().enumerate() {
+            let theta = 2. * PI * i as f64 / modes as f64;
+            *x += positions
+                .iter()
+                .map(|p| {
+                    //periodic distance to the soliton center
+                    let d = (theta - p + PI).rem_euclid(2. * PI) - PI;
+                    soliton / (inverse_width * d).cosh()
+                })
+                .sum::<Complex64>();
+        }
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::LossProfile;
+
+    #[test]
+    fn cw_background_includes_mode_loss() {
+        let mut property = WorkerProperty::new(16, 0);
+        property.components[0].alpha = 1.;
+        property.components[0].loss_profile = LossProfile::Band {
+            center: 0.,
+            width: 2.,
+            depth: 0.5,
+        };
+        let states = InitialCondition::CwSteady
+            .generate(Topology::Single, &property, &mut StdRng::seed_from_u64(0))
+            .unwrap();
+        let p = &property.components[0];
+        let loss = p.loss + 0.5;
+        let pump = Complex64::from_polar(p.pump, p.pump_phase);
+        let power = steady::cw_intensities(p.alpha, loss, p.pump)[0];
+        let expected = steady::cw_field(p.alpha, loss, pump, power);
+        assert_eq!(states.len(), 1);
+        assert!(states[0].iter().all(|x| (x - expected).norm() < 1e-12));
+    }
+
+    #[test]
+    fn cw_background_of_coupled_components() {
+        let mut property = WorkerProperty::new(16, 0);
+        property.components[1].pump = 1.;
+        let states = InitialCondition::CwSteady
+            .generate(Topology::Coupled, &property, &mut StdRng::seed_from_u64(0))
+            .unwrap();
+        let lower = &steady::cw_states(Topology::Coupled, &property)[0];
+        assert_eq!(states.len(), 2);
+        for (s, &b) in states.iter().zip(lower.fields.iter()) {
+            assert!(s.iter().all(|&x| x == b));
+        }
+        //the coupling and the second pump move the state away from the uncoupled one
+        let p = &property.components[0];
+        let power = steady::cw_intensities(p.alpha, p.loss, p.pump)[0];
+        assert!((lower.fields[0].norm_sqr() - power).abs() > 1e-3);
+    }
+}
@@ -1,24 +1,94 @@
-use std::f64::consts::PI;
+use std::{collections::BTreeMap, f64::consts::PI};
 
 pub use anyhow::{anyhow, Result};
-use lle::{num_complex::Complex64, CoupleOp, CoupledLleSolver, Evolver, LinearOp, LleSolver};
+use lle::{num_complex::Complex64, CoupleOp, CoupledLleSolver, Evolver, Freq, LleSolver};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+mod batch;
+mod config;
+mod couple;
+mod drive;
+mod init;
+mod linear;
+mod loss;
+mod mi;
+mod noise;
+mod nonlinear;
+mod pump;
+mod scan;
+mod snapshot;
+mod steady;
+mod sweep;
+mod table;
+mod thermal;
+pub use batch::{Batch, BatchLength};
+pub use config::{ComponentConfig, Config, CouplingConfig, DispersionConfig, DriveConfig};
+pub use couple::Coupling;
+use couple::{CrossPhaseMod, ModeCrossings};
+pub use drive::Drive;
+use drive::Drives;
+pub use init::InitialCondition;
+use linear::ComponentLinear;
+pub use loss::{LossParameter, LossProfile};
+pub use mi::{mi_gain, roll_number};
+pub use noise::NoiseModel;
+use noise::NoiseSource;
+use nonlinear::Kerr;
+pub use nonlinear::Raman;
+use pump::Pump;
+pub use pump::{PumpEnvelope, PumpParameter, PumpShape};
+pub use scan::{Scan, ScanPoint, ScanProtocol, ScanSegment};
+pub use snapshot::Snapshot;
+pub use steady::{cw_field, cw_intensities, cw_states, s_curve, CwState};
+pub use sweep::{Regime, Sweep, SweepAxis, SweepMap, SweepPoint};
+pub use table::{mode_range, ModeTable};
+pub use thermal::Thermal;
+
+type ComponentSolver = LleSolver<f64, Vec<Complex64>, ComponentLinear, Kerr, Pump, Drives>;
+
+type CoupledCore = CoupledLleSolver<
+    f64,
+    Vec<Complex64>,
+    Vec<Complex64>,
+    ComponentLinear,
+    ComponentLinear,
+    Kerr,
+    Kerr,
+    Pump,
+    Pump,
+    Drives,
+    Drives,
+    lle::CoupleOpWithNonLinear<ModeCrossings, CrossPhaseMod>,
+>;
+
+enum Core {
+    Single(Box<ComponentSolver>),
+    Coupled(Box<CoupledCore>),
+}
+
+///which kind of resonator system the [`Worker`] simulates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Topology {
+    ///a single resonator described by one LLE
+    Single,
+    ///two resonators coupled by mode splitting at selected modes
+    #[default]
+    Coupled,
+}
 
 pub struct Worker {
-    core: CoupledLleSolver<
-        f64,
-        [Complex64; SHELL_LEN],
-        [Complex64; SHELL_LEN],
-        lle::LinearOpAdd<f64, (lle::DiffOrder, Complex64), (lle::DiffOrder, Complex64)>,
-        lle::LinearOpAdd<f64, (lle::DiffOrder, Complex64), (lle::DiffOrder, Complex64)>,
-        lle::SPhaMod,
-        lle::SPhaMod,
-        Complex64,
-        lle::NoneOp<f64>,
-        lle::NoneOp<f64>,
-        lle::NoneOp<f64>,
-        lle::CoupleOpWithNonLinear<lle::ModeSplit<f64>, lle::XPhaMod>,
-    >,
+    core: Core,
     property: WorkerProperty,
+    ///temperature of each component, driving the thermal detuning shift
+    temperature: [f64; 2],
+    ///the latest detuning scan, kept after it finished for its record
+    scan: Option<Scan>,
+    ///simulation time elapsed, the slow time of the pump envelopes
+    time: f64,
+    ///source of all randomness, seeded by [`WorkerProperty::seed`]
+    rng: StdRng,
+    noise: NoiseSource,
 }
 pub struct CursorPos {
     convert: Box<dyn Fn((i32, i32)) -> Option<(f64, f64)>>,
@@ -35,147 +105,988 @@ pub struct Point {
     pub y: f64,
 }
 
+///one of the coupled resonators, [`Topology::Single`] only uses the first one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
+    First,
+    Second,
+}
+
+impl Component {
+    pub const ALL: [Component; 2] = [Component::First, Component::Second];
+    pub fn index(self) -> usize {
+        match self {
+            Component::First => 0,
+            Component::Second => 1,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum WorkerUpdate {
-    Alpha(f64),
-    Pump(f64),
-    Linear(f64),
+    Alpha(Component, f64),
+    Pump(Component, f64),
+    PumpPhase(Component, f64),
+    PumpParameter(Component, PumpParameter, f64),
+    ///amplitude of the auxiliary drive at a simulated mode, adding the drive if absent
+    DriveAmplitude(Component, Freq, f64),
+    ///frequency offset of the auxiliary drive at a simulated mode, adding the drive if absent
+    DriveOffset(Component, Freq, f64),
+    Drift(Component, f64),
+    Linear(Component, f64),
+    ///coefficient of a dispersion term with order higher than 2, enabling it if absent
+    Dispersion(Component, u32, f64),
+    Loss(Component, f64),
+    LossParameter(Component, LossParameter, f64),
+    RamanFraction(Component, f64),
+    RamanTime(Component, f64),
+    ShockTime(Component, f64),
+    ThermalTime(Component, f64),
+    ThermalCoefficient(Component, f64),
     RecordStep(u32),
     SimuStep(f64),
-    Couple(f64),
+    ///strength of the coupling at a mode, adding the coupling if absent
+    Couple(Freq, f64),
+    ///phase of the coupling at a mode, adding the coupling if absent
+    CouplePhase(Freq, f64),
+    Xpm(f64),
+    Modes(usize),
+    ///amplitude of the noise model, ignored for [`NoiseModel::None`]
+    Noise(f64),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct WorkerProperty {
+///parameters of a single resonator, normalized to its half linewidth
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComponentProperty {
+    ///detuning of the pump laser from the pumped mode
     pub alpha: f64,
     pub pump: f64,
+    ///phase of the pump, relative to the one driving the first component
+    pub pump_phase: f64,
+    ///fast time profile of the pump, normalized to `pump`
+    pub pump_shape: PumpShape,
+    ///slow time variation of the pump, normalized to `pump`
+    pub pump_envelope: PumpEnvelope,
+    ///auxiliary lasers driving single modes, indexed by mode
+    pub drives: BTreeMap<Freq, Drive>,
+    ///first order dispersion, the drift velocity of the field relative to the reference frame
+    pub drift: f64,
+    ///second order dispersion
     pub linear: f64,
+    ///enabled higher order (>2) dispersion terms, indexed by order,
+    ///in the same convention as `linear`
+    pub dispersion: BTreeMap<u32, f64>,
+    ///measured integrated dispersion `D_int`, replacing the polynomial dispersion terms if set
+    pub dint: Option<ModeTable>,
+    ///intrinsic loss, 1 for the reference linewidth
+    pub loss: f64,
+    ///mode dependent loss added to `loss`
+    pub loss_profile: LossProfile,
+    ///delayed raman response, pure instantaneous kerr nonlinearity if `None`
+    pub raman: Option<Raman>,
+    ///self-steepening shock time in the normalized fast time, 0 to disable
+    pub shock_time: f64,
+    ///thermo-optic detuning dynamics, static detuning if `None`
+    pub thermal: Option<Thermal>,
+}
+
+impl ComponentProperty {
+    ///check the per-mode tables match the simulated mode number
+    pub fn check_modes(&self, modes: usize) -> Result<()> {
+        if let Some(ref t) = self.dint {
+            t.check_modes(modes)?;
+        }
+        self.loss_profile.check_modes(modes)
+    }
+    ///whether the driving terms vary in the slow time
+    pub fn is_time_dependent(&self) -> bool {
+        !self.pump_envelope.is_constant() || self.drives.values().any(|d| d.offset != 0.)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkerProperty {
+    pub components: [ComponentProperty; 2],
     pub record_step: u32,
     pub simu_step: f64,
-    pub couple: f64,
+    ///couplings between the two components, indexed by mode
+    pub couplings: BTreeMap<Freq, Coupling>,
+    ///cross phase modulation coefficient between the two components,
+    ///2 for counter-propagating fields, 2/3 for orthogonal polarizations and 0 to disable
+    pub xpm: f64,
+    pub modes: usize,
+    ///seed of the random initial state and noise, the same seed replays the same run
+    pub seed: u64,
+    pub noise: NoiseModel,
 }
 
-const SHELL_LEN: usize = 128;
+pub const DEFAULT_MODES: usize = 128;
 
-impl Worker {
-    pub fn new() -> Self {
+impl WorkerProperty {
+    ///the default parameters simulating `modes` modes
+    pub fn new(modes: usize, seed: u64) -> Self {
         const STEP_DIST: f64 = 8e-4;
         const PUMP: f64 = 3.94;
         const LINEAR: f64 = -0.0444;
         const ALPHA: f64 = -5.;
         const COUPLE: f64 = 1.;
-        use rand::Rng;
-        let mut rand = rand::rng();
-        let mut init2 = [Complex64::new(0., 0.); SHELL_LEN];
-        let mut init1 = init2.clone();
-        init1.iter_mut().for_each(|x| {
-            *x = (Complex64::i() * rand.random::<f64>() * 2. * PI).exp()
-                * (-(rand.random::<f64>() * 1e5).powi(2)).exp()
-        });
-        init2.iter_mut().for_each(|x| {
-            *x = (Complex64::i() * rand.random::<f64>() * 2. * PI).exp()
+        const XPM: f64 = 2.;
+        WorkerProperty {
+            components: [
+                ComponentProperty {
+                    alpha: ALPHA,
+                    pump: PUMP,
+                    pump_phase: 0.,
+                    pump_shape: PumpShape::Cw,
+                    pump_envelope: PumpEnvelope::Constant,
+                    drives: BTreeMap::new(),
+                    drift: 0.,
+                    linear: LINEAR,
+                    dispersion: BTreeMap::new(),
+                    dint: None,
+                    loss: 1.,
+                    loss_profile: LossProfile::Flat,
+                    raman: None,
+                    shock_time: 0.,
+                    thermal: None,
+                },
+                ComponentProperty {
+                    alpha: ALPHA,
+                    pump: 0.,
+                    pump_phase: 0.,
+                    pump_shape: PumpShape::Cw,
+                    pump_envelope: PumpEnvelope::Constant,
+                    drives: BTreeMap::new(),
+                    drift: 0.,
+                    linear: LINEAR,
+                    dispersion: BTreeMap::new(),
+                    dint: None,
+                    loss: 1.,
+                    loss_profile: LossProfile::Flat,
+                    raman: None,
+                    shock_time: 0.,
+                    thermal: None,
+                },
+            ],
+            record_step: 100,
+            simu_step: STEP_DIST,
+            couplings: std::iter::once((
+                0,
+                Coupling {
+                    strength: COUPLE,
+                    phase: 0.,
+                },
+            ))
+            .collect(),
+            xpm: XPM,
+            modes,
+            seed,
+            noise: NoiseModel::default(),
+        }
+    }
+    ///check the simulation steps, the loss profiles and the per-mode tables are valid
+    pub fn check(&self) -> Result<()> {
+        if self.modes == 0 {
+            return Err(anyhow!("mode number should be positive"));
+        }
+        if self.record_step == 0 {
+            return Err(anyhow!("record step should be positive"));
+        }
+        if !(self.simu_step > 0. && self.simu_step.is_finite()) {
+            return Err(anyhow!(
+                "simulation step should be positive, got {}",
+                self.simu_step
+            ));
+        }
+        for p in self.components.iter() {
+            p.loss_profile.check()?;
+            p.check_modes(self.modes)?;
+        }
+        Ok(())
+    }
+}
+
+///intracavity power averaged over the round trip
+fn average_power(state: &[Complex64]) -> f64 {
+    state.iter().map(|x| x.norm_sqr()).sum::<f64>() / state.len() as f64
+}
+
+fn finite(name: &str, value: f64) -> Result<f64> {
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(anyhow!("{} should be finite, got {}", name, value))
+    }
+}
+
+fn positive(name: &str, value: f64) -> Result<f64> {
+    if value > 0. && value.is_finite() {
+        Ok(value)
+    } else {
+        Err(anyhow!("{} should be positive, got {}", name, value))
+    }
+}
+
+fn non_negative(name: &str, value: f64) -> Result<f64> {
+    if value >= 0. && value.is_finite() {
+        Ok(value)
+    } else {
+        Err(anyhow!("{} should be non-negative, got {}", name, value))
+    }
+}
+
+fn check_mode(name: &str, mode: Freq, modes: usize) -> Result<()> {
+    let (lower, upper) = mode_range(modes);
+    if mode < lower || mode > upper {
+        return Err(anyhow!(
+            "{} at mode {} out of the simulated modes {}..={}",
+            name,
+            mode,
+            lower,
+            upper
+        ));
+    }
+    Ok(())
+}
+
+fn random_state(len: usize, rand: &mut impl Rng) -> Vec<Complex64> {
+    (0..len)
+        .map(|_| {
+            (Complex64::i() * rand.random::<f64>() * 2. * PI).exp()
                 * (-(rand.random::<f64>() * 1e5).powi(2)).exp()
-        });
-        let lle1 = LleSolver::builder()
-            .state(init1)
-            .step_dist(STEP_DIST)
-            .constant(Complex64::from(PUMP))
-            .linear(
-                (0, -(Complex64::i() * ALPHA + 1.))
-                    .add_linear_op((2, -Complex64::i() * LINEAR / 2.)),
-            )
-            .nonlin(lle::SPhaMod)
-            .constant_freq(lle::NoneOp::default())
-            .build();
-        let lle2 = LleSolver::builder()
-            .state(init2)
-            .step_dist(STEP_DIST)
-            .constant(lle::NoneOp::default())
-            .linear(
-                (0, -(Complex64::i() * ALPHA + 1.))
-                    .add_linear_op((2, -Complex64::i() * LINEAR / 2.)),
-            )
-            .nonlin(lle::SPhaMod)
-            .constant_freq(lle::NoneOp::default())
-            .build();
+        })
+        .collect()
+}
+
+impl Core {
+    fn component_mut(&mut self, component: Component) -> Option<&mut ComponentSolver> {
+        match (self, component) {
+            (Core::Single(c), Component::First) => Some(c.as_mut()),
+            (Core::Coupled(c), Component::First) => Some(&mut c.component1),
+            (Core::Coupled(c), Component::Second) => Some(&mut c.component2),
+            (Core::Single(_), Component::Second) => {
+                log::warn!("single resonator has no second component");
+                None
+            }
+        }
+    }
+    fn set_linear(&mut self, component: Component, linear: ComponentLinear) {
+        if let Some(c) = self.component_mut(component) {
+            c.linear = linear.into();
+        }
+    }
+    fn set_pump(&mut self, component: Component, pump: Pump) {
+        if let Some(c) = self.component_mut(component) {
+            c.constant = pump.into();
+        }
+    }
+    fn set_drives(&mut self, component: Component, drives: Drives) {
+        if let Some(c) = self.component_mut(component) {
+            c.constant_freq = drives.into();
+        }
+    }
+    fn set_drive_time(&mut self, component: Component, time: f64) {
+        if let Some(d) = self
+            .component_mut(component)
+            .and_then(|c| c.constant_freq.as_mut())
+        {
+            d.set_time(time);
+        }
+    }
+    fn set_pump_amplitude(&mut self, component: Component, amplitude: Complex64) {
+        if let Some(p) = self
+            .component_mut(component)
+            .and_then(|c| c.constant.as_mut())
+        {
+            p.set_amplitude(amplitude);
+        }
+    }
+    fn set_nonlinear(&mut self, component: Component, property: &ComponentProperty) {
+        if let Some(c) = self.component_mut(component) {
+            c.nonlin = Kerr::new(property).into();
+        }
+    }
+    fn set_step_dist(&mut self, step_dist: f64) {
+        match self {
+            Core::Single(c) => c.step_dist = step_dist,
+            Core::Coupled(c) => {
+                c.component1.step_dist = step_dist;
+                c.component2.step_dist = step_dist;
+            }
+        }
+    }
+    fn states(&self) -> Vec<&[Complex64]> {
+        match self {
+            Core::Single(c) => vec![c.state()],
+            Core::Coupled(c) => vec![c.component1.state(), c.component2.state()],
+        }
+    }
+    fn states_mut(&mut self) -> Vec<&mut [Complex64]> {
+        match self {
+            Core::Single(c) => vec![c.state_mut()],
+            Core::Coupled(c) => vec![c.component1.state_mut(), c.component2.state_mut()],
+        }
+    }
+    fn evolve_n(&mut self, n: u32) {
+        match self {
+            Core::Single(c) => c.evolve_n(n),
+            Core::Coupled(c) => c.evolve_n(n),
+        }
+    }
+}
+
+impl Worker {
+    ///a worker with a random seed, recorded in [`WorkerProperty::seed`]
+    pub fn new(topology: Topology, modes: usize) -> Self {
+        Self::with_seed(topology, modes, rand::random())
+    }
+    pub fn with_seed(topology: Topology, modes: usize, seed: u64) -> Self {
+        Self::build(topology, WorkerProperty::new(modes, seed))
+    }
+    ///a worker simulating `property`, starting from a random state drawn with its seed
+    pub fn from_property(topology: Topology, property: WorkerProperty) -> Result<Self> {
+        property.check()?;
+        Ok(Self::build(topology, property))
+    }
+    fn build(topology: Topology, property: WorkerProperty) -> Self {
+        let mut rng = StdRng::seed_from_u64(property.seed);
         Worker {
-            core: CoupledLleSolver::builder()
-                .component1(lle1)
-                .component2(lle2)
-                .couple(
-                    lle::ModeSplit::builder()
-                        .strength(COUPLE)
-                        .mode(0)
-                        .build()
-                        .with_nonlinear(lle::XPhaMod),
-                )
-                .build(),
-
-            property: WorkerProperty {
-                alpha: ALPHA,
-                linear: LINEAR,
-                pump: PUMP,
-                record_step: 100,
-                simu_step: STEP_DIST,
-                couple: COUPLE,
-            },
-        }
-    }
-    pub fn get_property(&self) -> WorkerProperty {
-        self.property
+            core: Self::build_core(topology, &property, 0., &mut rng),
+            noise: NoiseSource::new(property.modes),
+            property,
+            temperature: [0.; 2],
+            scan: None,
+            time: 0.,
+            rng,
+        }
+    }
+    fn build_component(
+        property: &WorkerProperty,
+        component: Component,
+        time: f64,
+        rng: &mut StdRng,
+    ) -> ComponentSolver {
+        let p = &property.components[component.index()];
+        LleSolver::builder()
+            .state(random_state(property.modes, rng))
+            .step_dist(property.simu_step)
+            .constant(Pump::new(p, property.modes, time))
+            .linear(ComponentLinear::new(p))
+            .nonlin(Kerr::new(p))
+            .constant_freq(Drives::new(&p.drives, time))
+            .build()
+    }
+    ///build a new solver from `property` at simulation `time`,
+    ///the states are initialized with random noise
+    fn build_core(
+        topology: Topology,
+        property: &WorkerProperty,
+        time: f64,
+        rng: &mut StdRng,
+    ) -> Core {
+        let lle1 = Self::build_component(property, Component::First, time, rng);
+        match topology {
+            Topology::Single => Core::Single(Box::new(lle1)),
+            Topology::Coupled => Core::Coupled(Box::new(
+                CoupledLleSolver::builder()
+                    .component1(lle1)
+                    .component2(Self::build_component(
+                        property,
+                        Component::Second,
+                        time,
+                        rng,
+                    ))
+                    .couple(
+                        ModeCrossings::new(&property.couplings).with_nonlinear(CrossPhaseMod {
+                            coefficient: property.xpm,
+                        }),
+                    )
+                    .build(),
+            )),
+        }
+    }
+    pub fn topology(&self) -> Topology {
+        match self.core {
+            Core::Single(_) => Topology::Single,
+            Core::Coupled(_) => Topology::Coupled,
+        }
+    }
+    ///components simulated by the current topology
+    pub fn components(&self) -> &'static [Component] {
+        match self.core {
+            Core::Single(_) => &Component::ALL[..1],
+            Core::Coupled(_) => &Component::ALL,
+        }
+    }
+    pub fn get_property(&self) -> &WorkerProperty {
+        &self.property
     }
     pub fn set_property(&mut self, update: WorkerUpdate) {
         match update {
-            WorkerUpdate::Alpha(value) => {
-                self.property.alpha = value;
-                self.core.component1.linear = (0, -(Complex64::i() * self.property.alpha + 1.))
-                    .add_linear_op((2, -Complex64::i() * self.property.linear / 2.))
-                    .into();
-                self.core.component2.linear = (0, -(Complex64::i() * self.property.alpha + 1.))
-                    .add_linear_op((2, -Complex64::i() * self.property.linear / 2.))
-                    .into()
-            }
-            WorkerUpdate::Pump(value) => {
-                self.property.pump = value;
-                self.core.component1.constant = Complex64::from(value).into();
-            }
-            WorkerUpdate::Linear(value) => {
-                self.property.linear = value;
-                self.core.component1.linear = (0, -(Complex64::i() * self.property.alpha + 1.))
-                    .add_linear_op((2, -Complex64::i() * self.property.linear / 2.))
-                    .into();
-                self.core.component2.linear = (0, -(Complex64::i() * self.property.alpha + 1.))
-                    .add_linear_op((2, -Complex64::i() * self.property.linear / 2.))
-                    .into()
-            }
-            WorkerUpdate::RecordStep(value) => self.property.record_step = value as u32,
+            WorkerUpdate::Alpha(c, value) => {
+                self.property.components[c.index()].alpha = value;
+                self.update_linear(c);
+            }
+            WorkerUpdate::Pump(c, value) => {
+                self.property.components[c.index()].pump = value;
+                self.update_pump_amplitude(c);
+            }
+            WorkerUpdate::PumpPhase(c, value) => {
+                self.property.components[c.index()].pump_phase = value;
+                self.update_pump_amplitude(c);
+            }
+            WorkerUpdate::PumpParameter(c, parameter, value) => {
+                let p = &mut self.property.components[c.index()];
+                let set = p
+                    .pump_shape
+                    .set_parameter(parameter, value)
+                    .and_then(|set| Ok(set || p.pump_envelope.set_parameter(parameter, value)?));
+                match set {
+                    Ok(true) => (),
+                    Ok(false) => {
+                        log::warn!(
+                            "pump of component {} has no parameter {:?}",
+                            c.index() + 1,
+                            parameter
+                        );
+                        return;
+                    }
+                    Err(e) => {
+                        log::warn!("{}", e);
+                        return;
+                    }
+                }
+                self.update_pump(c);
+            }
+            WorkerUpdate::Drift(c, value) => {
+                self.property.components[c.index()].drift = value;
+                self.update_linear(c);
+            }
+            WorkerUpdate::Linear(c, value) => {
+                self.property.components[c.index()].linear = value;
+                self.update_linear(c);
+            }
+            WorkerUpdate::Dispersion(c, order, value) => {
+                if order <= 2 {
+                    log::warn!("dispersion order should be higher than 2, got {}", order);
+                    return;
+                }
+                self.property.components[c.index()]
+                    .dispersion
+                    .insert(order, value);
+                self.update_linear(c);
+            }
+            WorkerUpdate::Loss(c, value) => {
+                if let Err(e) = non_negative("loss", value) {
+                    log::warn!("{}", e);
+                    return;
+                }
+                self.property.components[c.index()].loss = value;
+                self.update_linear(c);
+            }
+            WorkerUpdate::LossParameter(c, parameter, value) => {
+                match self.property.components[c.index()]
+                    .loss_profile
+                    .set_parameter(parameter, value)
+                {
+                    Ok(true) => (),
+                    Ok(false) => {
+                        log::warn!(
+                            "loss profile of component {} has no parameter {:?}",
+                            c.index() + 1,
+                            parameter
+                        );
+                        return;
+                    }
+                    Err(e) => {
+                        log::warn!("{}", e);
+                        return;
+                    }
+                }
+                self.update_linear(c);
+            }
+            WorkerUpdate::RamanFraction(c, value) => {
+                if !(0. ..=1.).contains(&value) {
+                    log::warn!("raman fraction should be in [0, 1], got {}", value);
+                    return;
+                }
+                match self.property.components[c.index()].raman {
+                    Some(ref mut r) => r.fraction = value,
+                    None => {
+                        log::warn!("raman response of component {} disabled", c.index() + 1);
+                        return;
+                    }
+                }
+                self.core
+                    .set_nonlinear(c, &self.property.components[c.index()]);
+            }
+            WorkerUpdate::RamanTime(c, value) => {
+                if value <= 0. {
+                    log::warn!("raman response time should be positive, got {}", value);
+                    return;
+                }
+                match self.property.components[c.index()].raman {
+                    Some(ref mut r) => r.time = value,
+                    None => {
+                        log::warn!("raman response of component {} disabled", c.index() + 1);
+                        return;
+                    }
+                }
+                self.core
+                    .set_nonlinear(c, &self.property.components[c.index()]);
+            }
+            WorkerUpdate::ShockTime(c, value) => {
+                if let Err(e) = non_negative("shock time", value) {
+                    log::warn!("{}", e);
+                    return;
+                }
+                self.property.components[c.index()].shock_time = value;
+                self.core
+                    .set_nonlinear(c, &self.property.components[c.index()]);
+            }
+            WorkerUpdate::ThermalTime(c, value) => {
+                if value <= 0. {
+                    log::warn!("thermal relaxation time should be positive, got {}", value);
+                    return;
+                }
+                match self.property.components[c.index()].thermal {
+                    Some(ref mut t) => t.time = value,
+                    None => log::warn!("thermal effect of component {} disabled", c.index() + 1),
+                }
+            }
+            WorkerUpdate::ThermalCoefficient(c, value) => {
+                match self.property.components[c.index()].thermal {
+                    Some(ref mut t) => t.coefficient = value,
+                    None => {
+                        log::warn!("thermal effect of component {} disabled", c.index() + 1);
+                        return;
+                    }
+                }
+                self.update_linear(c);
+            }
+            WorkerUpdate::RecordStep(value) => self.property.record_step = value,
             WorkerUpdate::SimuStep(value) => {
                 self.property.simu_step = value;
-                self.core.component1.step_dist = value;
-                self.core.component2.step_dist = value;
+                self.core.set_step_dist(value);
+            }
+            WorkerUpdate::Couple(mode, value) => {
+                self.property
+                    .couplings
+                    .entry(mode)
+                    .or_insert(Coupling {
+                        strength: 0.,
+                        phase: 0.,
+                    })
+                    .strength = value;
+                self.update_couple();
+            }
+            WorkerUpdate::CouplePhase(mode, value) => {
+                self.property
+                    .couplings
+                    .entry(mode)
+                    .or_insert(Coupling {
+                        strength: 0.,
+                        phase: 0.,
+                    })
+                    .phase = value;
+                self.update_couple();
+            }
+            WorkerUpdate::DriveAmplitude(c, mode, value) => {
+                if let Err(e) = check_mode("drive", mode, self.property.modes) {
+                    log::warn!("{}", e);
+                    return;
+                }
+                self.property.components[c.index()]
+                    .drives
+                    .entry(mode)
+                    .or_insert(Drive {
+                        amplitude: 0.,
+                        offset: 0.,
+                    })
+                    .amplitude = value;
+                self.update_drives(c);
             }
-            WorkerUpdate::Couple(value) => {
-                self.property.couple = value;
-                self.core.couple.couple.strength = value;
+            WorkerUpdate::DriveOffset(c, mode, value) => {
+                if let Err(e) = check_mode("drive", mode, self.property.modes) {
+                    log::warn!("{}", e);
+                    return;
+                }
+                self.property.components[c.index()]
+                    .drives
+                    .entry(mode)
+                    .or_insert(Drive {
+                        amplitude: 0.,
+                        offset: 0.,
+                    })
+                    .offset = value;
+                self.update_drives(c);
+            }
+            WorkerUpdate::Xpm(value) => {
+                self.property.xpm = value;
+                match self.core {
+                    Core::Coupled(ref mut c) => c.couple.nonlinear.coefficient = value,
+                    Core::Single(_) => {
+                        log::warn!("cross phase modulation has no effect on a single resonator")
+                    }
+                }
+            }
+            WorkerUpdate::Noise(value) => {
+                if !self.property.noise.set_amplitude(value) {
+                    log::warn!("noise disabled, ignored setting noise amplitude");
+                }
+            }
+            WorkerUpdate::Modes(value) => {
+                if value == 0 {
+                    log::warn!("ignored setting mode number to 0");
+                    return;
+                }
+                if let Some((c, e)) = Component::ALL.iter().find_map(|&c| {
+                    self.property.components[c.index()]
+                        .check_modes(value)
+                        .err()
+                        .map(|e| (c, e))
+                }) {
+                    log::warn!(
+                        "ignored setting mode number to {}, table of component {}: {}",
+                        value,
+                        c.index() + 1,
+                        e
+                    );
+                    return;
+                }
+                if value != self.property.modes {
+                    self.property.modes = value;
+                    self.core =
+                        Self::build_core(self.topology(), &self.property, self.time, &mut self.rng);
+                    self.temperature = [0.; 2];
+                }
             }
         }
     }
+    ///disable the dispersion term of `order` of component `c`
+    pub fn remove_dispersion(&mut self, c: Component, order: u32) {
+        if self.property.components[c.index()]
+            .dispersion
+            .remove(&order)
+            .is_some()
+        {
+            self.update_linear(c);
+        }
+    }
+    fn update_linear(&mut self, c: Component) {
+        let linear = ComponentLinear::new(&self.property.components[c.index()])
+            .shift_detuning(self.detuning_shift(c));
+        self.core.set_linear(c, linear);
+    }
+    fn update_pump(&mut self, c: Component) {
+        let pump = Pump::new(
+            &self.property.components[c.index()],
+            self.property.modes,
+            self.time,
+        );
+        self.core.set_pump(c, pump);
+    }
+    ///refresh the pump amplitude only, keeping the sampled fast time shape
+    fn update_pump_amplitude(&mut self, c: Component) {
+        let amplitude = Pump::amplitude(&self.property.components[c.index()], self.time);
+        self.core.set_pump_amplitude(c, amplitude);
+    }
+    fn update_drives(&mut self, c: Component) {
+        let drives = Drives::new(&self.property.components[c.index()].drives, self.time);
+        self.core.set_drives(c, drives);
+    }
+    fn update_couple(&mut self) {
+        match self.core {
+            Core::Coupled(ref mut c) => {
+                c.couple.couple = ModeCrossings::new(&self.property.couplings)
+            }
+            Core::Single(_) => log::warn!("coupling has no effect on a single resonator"),
+        }
+    }
+    ///remove the coupling between the two components at `mode`
+    pub fn remove_coupling(&mut self, mode: Freq) {
+        if self.property.couplings.remove(&mode).is_some() {
+            self.update_couple();
+        }
+    }
+    ///replace the polynomial dispersion of component `c` by a measured `D_int` table,
+    ///or restore it with `None`
+    pub fn set_dint(&mut self, c: Component, table: Option<ModeTable>) -> Result<()> {
+        if let Some(ref t) = table {
+            t.check_modes(self.property.modes)?;
+        }
+        self.property.components[c.index()].dint = table;
+        self.update_linear(c);
+        Ok(())
+    }
+    pub fn set_loss_profile(&mut self, c: Component, profile: LossProfile) -> Result<()> {
+        profile.check()?;
+        profile.check_modes(self.property.modes)?;
+        self.property.components[c.index()].loss_profile = profile;
+        self.update_linear(c);
+        Ok(())
+    }
+    ///enable the delayed raman response of component `c`, or disable it with `None`
+    pub fn set_raman(&mut self, c: Component, raman: Option<Raman>) {
+        self.property.components[c.index()].raman = raman;
+        self.core
+            .set_nonlinear(c, &self.property.components[c.index()]);
+    }
+    ///enable the thermal dynamics of component `c`, or disable it with `None`,
+    ///the temperature is reset when disabled
+    pub fn set_thermal(&mut self, c: Component, thermal: Option<Thermal>) {
+        if thermal.is_none() {
+            self.temperature[c.index()] = 0.;
+        }
+        self.property.components[c.index()].thermal = thermal;
+        self.update_linear(c);
+    }
+    pub fn temperature(&self, c: Component) -> f64 {
+        self.temperature[c.index()]
+    }
+    ///detuning shift of component `c` by its current temperature, 0 if the thermal effect is disabled
+    pub fn detuning_shift(&self, c: Component) -> f64 {
+        self.property.components[c.index()]
+            .thermal
+            .map_or(0., |t| t.detuning_shift(self.temperature[c.index()]))
+    }
+    ///relax the temperatures to the average intracavity power over `duration`,
+    ///the power is taken as constant as the thermal time is much longer than a tick
+    fn update_thermal(&mut self, duration: f64) {
+        let powers = self
+            .core
+            .states()
+            .into_iter()
+            .map(average_power)
+            .collect::<Vec<_>>();
+        for (&c, power) in Component::ALL.iter().zip(powers) {
+            if let Some(t) = self.property.components[c.index()].thermal {
+                self.temperature[c.index()] = t.relax(self.temperature[c.index()], power, duration);
+                self.update_linear(c);
+            }
+        }
+    }
+    ///remove the auxiliary drive of component `c` at `mode`
+    pub fn remove_drive(&mut self, c: Component, mode: Freq) {
+        if self.property.components[c.index()]
+            .drives
+            .remove(&mode)
+            .is_some()
+        {
+            self.update_drives(c);
+        }
+    }
+    pub fn set_pump_shape(&mut self, c: Component, shape: PumpShape) {
+        self.property.components[c.index()].pump_shape = shape;
+        self.update_pump(c);
+    }
+    pub fn set_pump_envelope(&mut self, c: Component, envelope: PumpEnvelope) {
+        self.property.components[c.index()].pump_envelope = envelope;
+        self.update_pump_amplitude(c);
+    }
+    ///restart the simulation from a random state drawn with `seed`,
+    ///the time, temperatures and detuning scan are reset
+    pub fn restart(&mut self, seed: u64) {
+        self.property.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+        self.time = 0.;
+        self.temperature = [0.; 2];
+        self.scan = None;
+        self.core = Self::build_core(self.topology(), &self.property, 0., &mut self.rng);
+    }
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            topology: self.topology(),
+            property: self.property.clone(),
+            time: self.time,
+            temperature: self.temperature,
+            states: self
+                .core
+                .states()
+                .into_iter()
+                .map(snapshot::encode_state)
+                .collect(),
+        }
+    }
+    ///restore a worker from `snapshot`, the random generator restarts from its seed
+    pub fn from_snapshot(snapshot: Snapshot) -> Result<Self> {
+        let Snapshot {
+            topology,
+            property,
+            time,
+            temperature,
+            states,
+        } = snapshot;
+        let components = match topology {
+            Topology::Single => 1,
+            Topology::Coupled => 2,
+        };
+        if states.len() != components {
+            return Err(anyhow!(
+                "{:?} resonator expects {} fields, got {}",
+                topology,
+                components,
+                states.len()
+            ));
+        }
+        if let Some(s) = states.iter().find(|s| s.len() != property.modes) {
+            return Err(anyhow!(
+                "field of {} points while simulating {} modes",
+                s.len(),
+                property.modes
+            ));
+        }
+        property.check()?;
+        let mut rng = StdRng::seed_from_u64(property.seed);
+        let mut worker = Worker {
+            core: Self::build_core(topology, &property, time, &mut rng),
+            noise: NoiseSource::new(property.modes),
+            property,
+            temperature,
+            scan: None,
+            time,
+            rng,
+        };
+        for (s, saved) in worker.core.states_mut().into_iter().zip(states.iter()) {
+            s.copy_from_slice(&snapshot::decode_state(saved));
+        }
+        for &c in worker.components() {
+            worker.update_linear(c);
+        }
+        Ok(worker)
+    }
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        self.snapshot().save(path)
+    }
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        Self::from_snapshot(Snapshot::load(path)?)
+    }
+    ///re-initialize the field of each component, keeping the parameters and time
+    pub fn reset(&mut self, init: &InitialCondition) -> Result<()> {
+        let states = init.generate(self.topology(), &self.property, &mut self.rng)?;
+        for (s, new) in self.core.states_mut().into_iter().zip(states) {
+            s.copy_from_slice(&new);
+        }
+        Ok(())
+    }
+    ///simulation time elapsed since the worker was created
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+    ///start scanning the laser detuning, replacing the previous scan and its record,
+    ///the first component follows `protocol` and the others keep their detuning offset to it
+    pub fn start_scan(&mut self, protocol: ScanProtocol) -> Result<()> {
+        let scan = Scan::new(protocol, self.property.seed)?;
+        self.set_detuning(scan.protocol().start);
+        self.scan = Some(scan);
+        Ok(())
+    }
+    pub fn stop_scan(&mut self) {
+        if let Some(ref mut s) = self.scan {
+            s.stop();
+        }
+    }
+    pub fn scan(&self) -> Option<&Scan> {
+        self.scan.as_ref()
+    }
+    fn set_detuning(&mut self, detuning: f64) {
+        let shift = detuning - self.property.components[Component::First.index()].alpha;
+        for &c in self.components() {
+            self.property.components[c.index()].alpha += shift;
+            self.update_linear(c);
+        }
+    }
+    pub fn set_noise(&mut self, noise: NoiseModel) {
+        self.property.noise = noise;
+    }
+    ///inject the noise accumulated over `duration` into each component
+    fn add_noise(&mut self, duration: f64) {
+        let noise = self.property.noise;
+        let (rng, source) = (&mut self.rng, &mut self.noise);
+        for (s, p) in self
+            .core
+            .states_mut()
+            .into_iter()
+            .zip(self.property.components.iter())
+        {
+            noise.apply(source, p, s, duration, rng);
+        }
+    }
     pub fn tick(&mut self) {
-        use rand::Rng;
-        let mut rand = rand::rng();
-        self.core.component1.state_mut().iter_mut().for_each(|x| {
-            *x += (Complex64::i() * rand.random::<f64>() * 2. * PI).exp()
-                * (-(rand.random::<f64>() * 1e5).powi(2)).exp()
-        });
-        self.core.component2.state_mut().iter_mut().for_each(|x| {
-            *x += (Complex64::i() * rand.random::<f64>() * 2. * PI).exp()
-                * (-(rand.random::<f64>() * 1e5).powi(2)).exp()
-        });
-        log::info!("tick steps {}", self.property.simu_step);
-        self.core.evolve_n(self.property.record_step);
-        log::info!("tick finished");
+        log::debug!("tick steps {}", self.property.simu_step);
+        let scanning = self.scan.as_ref().is_some_and(Scan::is_running);
+        let step = self.property.simu_step;
+        let varying = self
+            .components()
+            .iter()
+            .any(|c| self.property.components[c.index()].is_time_dependent());
+        let noisy = self.property.noise != NoiseModel::None;
+        if scanning || varying || noisy {
+            //time dependent parameters and noise are updated every step to follow them smoothly
+            for _ in 0..self.property.record_step {
+                self.core.evolve_n(1);
+                self.time += step;
+                if noisy {
+                    self.add_noise(step);
+                }
+                if let Some(d) = self.scan.as_mut().and_then(|s| s.advance(step)) {
+                    self.set_detuning(d);
+                }
+                for &c in self.components() {
+                    if self.property.components[c.index()].is_time_dependent() {
+                        self.update_pump_amplitude(c);
+                        self.core.set_drive_time(c, self.time);
+                    }
+                }
+            }
+        } else {
+            self.core.evolve_n(self.property.record_step);
+            self.time += self.property.record_step as f64 * step;
+        }
+        if scanning {
+            let detuning = self.property.components[Component::First.index()].alpha;
+            let power = self.core.states().into_iter().map(average_power).collect();
+            if let Some(ref mut s) = self.scan {
+                s.push(detuning, power);
+            }
+        }
+        self.update_thermal(self.property.record_step as f64 * self.property.simu_step);
+        log::debug!("tick finished");
     }
-    pub fn get_state(&self) -> (&[Complex64], &[Complex64]) {
-        (self.core.component1.state(), self.core.component2.state())
+    ///states of each component, one for [`Topology::Single`] and two for [`Topology::Coupled`]
+    pub fn get_state(&self) -> Vec<&[Complex64]> {
+        self.core.states()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejected_updates() {
+        let mut worker = Worker::with_seed(Topology::Single, 64, 1);
+        let c = Component::First;
+        let band = LossProfile::Band {
+            center: 0.,
+            width: 2.,
+            depth: 1.,
+        };
+        worker.set_loss_profile(c, band).unwrap();
+        let property = worker.get_property().clone();
+        for update in [
+            WorkerUpdate::Loss(c, -1.),
+            WorkerUpdate::Loss(c, f64::NAN),
+            WorkerUpdate::LossParameter(c, LossParameter::Width, 0.),
+            WorkerUpdate::LossParameter(c, LossParameter::Depth, -1.),
+            WorkerUpdate::LossParameter(c, LossParameter::Curvature, 1.),
+            WorkerUpdate::ShockTime(c, -1e-3),
+            WorkerUpdate::ShockTime(c, f64::INFINITY),
+            WorkerUpdate::DriveAmplitude(c, 32, 1.),
+            WorkerUpdate::DriveOffset(c, -33, 1.),
+        ] {
+            worker.set_property(update);
+            assert_eq!(worker.get_property(), &property, "{:?}", update);
+        }
     }
 }
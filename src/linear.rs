@@ -0,0 +1,57 @@
+use lle::{num_complex::Complex64, Freq, LinearOp, Step};
+
+use crate::{ComponentProperty, LossProfile, ModeTable};
+
+///frequency domain linear operator of one component,
+///the value at mode `μ` is `-(loss(μ) + i * alpha) + i * drift * μ + i * Σ d_n * μ^n / n!`,
+///the sum is replaced by `-D_int(μ)` if the integrated dispersion is given by a table
+#[derive(Debug, Clone)]
+pub struct ComponentLinear {
+    alpha: f64,
+    loss: f64,
+    loss_profile: LossProfile,
+    drift: f64,
+    ///(order, coefficient) pairs, second order term included
+    dispersion: Vec<(u32, f64)>,
+    dint: Option<ModeTable>,
+}
+
+impl ComponentLinear {
+    pub fn new(property: &ComponentProperty) -> Self {
+        Self {
+            alpha: property.alpha,
+            loss: property.loss,
+            loss_profile: property.loss_profile.clone(),
+            drift: property.drift,
+            dispersion: std::iter::once((2, property.linear))
+                .chain(property.dispersion.iter().map(|(&o, &d)| (o, d)))
+                .collect(),
+            dint: property.dint.clone(),
+        }
+    }
+    ///shift the detuning from the property, by the thermal effect
+    pub fn shift_detuning(mut self, shift: f64) -> Self {
+        self.alpha += shift;
+        self
+    }
+}
+
+fn factorial(n: u32) -> f64 {
+    (1..=n).map(f64::from).product()
+}
+
+impl LinearOp<f64> for ComponentLinear {
+    fn get_value(&self, _step: Step, freq: Freq) -> Complex64 {
+        let mu = freq as f64;
+        let dispersion = match self.dint {
+            Some(ref t) => -t.get(freq).unwrap_or_default(),
+            None => self
+                .dispersion
+                .iter()
+                .map(|&(order, d)| d * mu.powi(order as i32) / factorial(order))
+                .sum::<f64>(),
+        };
+        -Complex64::new(self.loss + self.loss_profile.value(freq), self.alpha)
+            + Complex64::i() * (self.drift * mu + dispersion)
+    }
+}
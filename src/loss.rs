@@ -0,0 +1,189 @@
+use lle::Freq;
+use serde::{Deserialize, Serialize};
+
+use crate::{finite, non_negative, positive, ModeTable, Result};
+
+///mode dependent loss added to the flat loss of a component
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum LossProfile {
+    #[default]
+    Flat,
+    ///coupling rate roll-off, growing as `curvature * μ²`
+    RollOff { curvature: f64 },
+    ///lorentzian absorption band with peak loss `depth` at mode `center`
+    Band { center: f64, width: f64, depth: f64 },
+    ///extra loss of each mode loaded from a table
+    Table(ModeTable),
+}
+
+///scalar parameters of the analytical [`LossProfile`]s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LossParameter {
+    Curvature,
+    Center,
+    Width,
+    Depth,
+}
+
+impl LossProfile {
+    pub fn value(&self, mode: Freq) -> f64 {
+        let mu = mode as f64;
+        match self {
+            LossProfile::Flat => 0.,
+            LossProfile::RollOff { curvature } => curvature * mu * mu,
+            LossProfile::Band {
+                center,
+                width,
+                depth,
+            } => depth / (1. + ((mu - center) * 2. / width).powi(2)),
+            LossProfile::Table(t) => t.get(mode).unwrap_or_default(),
+        }
+    }
+    pub fn parameters(&self) -> Vec<(LossParameter, f64)> {
+        match *self {
+            LossProfile::Flat | LossProfile::Table(_) => Vec::new(),
+            LossProfile::RollOff { curvature } => vec![(LossParameter::Curvature, curvature)],
+            LossProfile::Band {
+                center,
+                width,
+                depth,
+            } => vec![
+                (LossParameter::Center, center),
+                (LossParameter::Width, width),
+                (LossParameter::Depth, depth),
+            ],
+        }
+    }
+    ///set a parameter of the profile, returning `false` if the profile doesn't have it
+    ///and leaving the profile unchanged if the new one is invalid
+    pub fn set_parameter(&mut self, parameter: LossParameter, value: f64) -> Result<bool> {
+        let mut profile = self.clone();
+        match (&mut profile, parameter) {
+            (LossProfile::RollOff { curvature }, LossParameter::Curvature) => *curvature = value,
+            (LossProfile::Band { center, .. }, LossParameter::Center) => *center = value,
+            (LossProfile::Band { width, .. }, LossParameter::Width) => *width = value,
+            (LossProfile::Band { depth, .. }, LossParameter::Depth) => *depth = value,
+            _ => return Ok(false),
+        }
+        profile.check()?;
+        *self = profile;
+        Ok(true)
+    }
+    ///check the profile gives a finite and non-negative loss at every mode
+    pub fn check(&self) -> Result<()> {
+        match self {
+            LossProfile::Flat => (),
+            LossProfile::RollOff { curvature } => {
+                non_negative("loss curvature", *curvature)?;
+            }
+            LossProfile::Band {
+                center,
+                width,
+                depth,
+            } => {
+                finite("loss band center", *center)?;
+                positive("loss band width", *width)?;
+                non_negative("loss band depth", *depth)?;
+            }
+            LossProfile::Table(t) => {
+                for (mode, value) in t.iter() {
+                    non_negative(&format!("loss of mode {}", mode), value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    pub fn check_modes(&self, modes: usize) -> Result<()> {
+        match self {
+            LossProfile::Table(t) => t.check_modes(modes),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(content: &str) -> LossProfile {
+        LossProfile::Table(ModeTable::parse_csv(content).unwrap())
+    }
+
+    #[test]
+    fn value() {
+        assert_eq!(LossProfile::Flat.value(5), 0.);
+        let roll_off = LossProfile::RollOff { curvature: 0.5 };
+        assert_eq!(roll_off.value(0), 0.);
+        assert_eq!(roll_off.value(-4), 8.);
+        let band = LossProfile::Band {
+            center: 3.,
+            width: 4.,
+            depth: 2.,
+        };
+        assert_eq!(band.value(3), 2.);
+        //half maximum at half the width from the center
+        assert_eq!(band.value(1), 1.);
+        assert_eq!(band.value(5), 1.);
+        let table = table("-1,0.5\n0,0\n1,0.25");
+        assert_eq!(table.value(-1), 0.5);
+        assert_eq!(table.value(1), 0.25);
+        assert_eq!(table.value(7), 0.);
+    }
+
+    #[test]
+    fn check() {
+        assert!(LossProfile::Flat.check().is_ok());
+        assert!(LossProfile::RollOff { curvature: 0. }.check().is_ok());
+        assert!(LossProfile::RollOff { curvature: -1. }.check().is_err());
+        assert!(LossProfile::RollOff {
+            curvature: f64::INFINITY
+        }
+        .check()
+        .is_err());
+        let band = |center, width, depth| {
+            LossProfile::Band {
+                center,
+                width,
+                depth,
+            }
+            .check()
+        };
+        assert!(band(-3., 2., 0.).is_ok());
+        assert!(band(0., 0., 1.).is_err());
+        assert!(band(0., -2., 1.).is_err());
+        assert!(band(0., 2., -1.).is_err());
+        assert!(band(f64::NAN, 2., 1.).is_err());
+        assert!(table("0,0\n1,0.5").check().is_ok());
+        assert!(table("0,0\n1,-0.5").check().is_err());
+        assert!(table("0,0\n1,inf").check().is_err());
+    }
+
+    #[test]
+    fn set_parameter() {
+        let mut band = LossProfile::Band {
+            center: 0.,
+            width: 2.,
+            depth: 1.,
+        };
+        assert!(band.set_parameter(LossParameter::Width, 4.).unwrap());
+        assert!(band.set_parameter(LossParameter::Width, 0.).is_err());
+        assert!(band.set_parameter(LossParameter::Depth, -1.).is_err());
+        assert!(band.set_parameter(LossParameter::Center, f64::NAN).is_err());
+        //rejected values leave the profile untouched
+        assert_eq!(
+            band,
+            LossProfile::Band {
+                center: 0.,
+                width: 4.,
+                depth: 1.,
+            }
+        );
+        assert!(!band.set_parameter(LossParameter::Curvature, 1.).unwrap());
+        let mut roll_off = LossProfile::RollOff { curvature: 1e-3 };
+        assert!(roll_off
+            .set_parameter(LossParameter::Curvature, -1e-3)
+            .is_err());
+        assert_eq!(roll_off, LossProfile::RollOff { curvature: 1e-3 });
+    }
+}
@@ -1,7 +1,10 @@
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use iced::widget::{button, column, container, row, text, Column, Container, Row};
+use iced::widget::{
+    button, checkbox, column, container, pick_list, row, scrollable, text, text_input, Column,
+    Container, Row,
+};
 use iced::Task;
 use iced::{Alignment, Element, Length};
 use lle_simulator::*;
@@ -17,73 +20,303 @@ fn main() -> Result<()> {
         .filter_level(log::LevelFilter::Info)
         .init();
 
+    let single = std::env::args().skip(1).any(|x| x == "--single");
+    let seed = match std::env::args().skip_while(|x| x != "--seed").nth(1) {
+        Some(s) => Some(
+            s.parse::<u64>()
+                .map_err(|e| anyhow::anyhow!("illegal seed '{}': {}", s, e))?,
+        ),
+        None => None,
+    };
+    let simulator = match std::env::args().skip_while(|x| x != "--config").nth(1) {
+        Some(path) => {
+            let mut config = Config::load(&path)?;
+            if single {
+                config.topology = Topology::Single;
+            }
+            if seed.is_some() {
+                config.seed = seed;
+            }
+            info!("loaded configuration {}", path);
+            config.build()?
+        }
+        None => {
+            let topology = if single {
+                Topology::Single
+            } else {
+                Topology::Coupled
+            };
+            match seed {
+                Some(seed) => Worker::with_seed(topology, DEFAULT_MODES, seed),
+                None => Worker::new(topology, DEFAULT_MODES),
+            }
+        }
+    };
+    info!("simulating {:?} resonator", simulator.topology());
+
     // 使用新的应用程序构建API
     let app = iced::application(
         LleSimulator::title,
         LleSimulator::update,
         LleSimulator::view,
     );
-    app.run()?;
+    app.run_with(move || (LleSimulator::new(simulator), Task::none()))?;
 
     Ok(())
 }
 
 struct LleSimulator {
     simulator: Worker,
-    draw1: DrawData,
-    draw2: DrawData,
-    panel: [Control; 6],
+    draws: Vec<DrawData>,
+    panel: Vec<Control>,
+    dispersion_order: [String; 2],
+    drive_mode: [String; 2],
+    dint_path: [String; 2],
+    loss_table_path: [String; 2],
+    couple_mode: String,
+    scan_form: ScanForm,
+    scan_record_path: String,
+    seed: String,
+    snapshot_path: String,
+    init_kind: InitKind,
+    init_positions: String,
+    bistability: Option<Bistability>,
+    mi_gain: Option<(CurvePlot, Option<WorkerProperty>)>,
     pause: bool,
     last_update: Option<Instant>,
 }
 
-impl Default for LleSimulator {
-    fn default() -> Self {
-        const WIDTH: usize = 640;
-        const HEIGHT: usize = 640;
-        use WorkerUpdate::*;
-        let simulation = Worker::new();
-        let proper = simulation.get_property();
-        let init_from_property = |p: WorkerUpdate| -> Control<f64> {
-            match p {
-                Alpha(v) => Control::new(Alpha, "Alpha", v.into()),
-                Pump(v) => Control::new(Pump, "Pump", v.into()),
-                Linear(v) => Control::new(Linear, "Linear", v.into()),
-                RecordStep(_) => Control::new(|x| RecordStep(x as u32), "Record Step", None),
-                SimuStep(_) => Control::new(SimuStep, "Simulation Step", None),
-                Couple(v) => Control::new(Couple, "Couple Coefficient", v.into()),
-            }
-        };
-        let simulator = Worker::new();
+const WINDOW_SIZE: (usize, usize) = (640, 640);
+
+///bistability curve window with the latest sampled steady states and the parameters it was drawn for
+struct Bistability {
+    plot: CurvePlot,
+    curve: Option<SCurve>,
+    ///a sampling is running in the background
+    sampling: bool,
+    drawn: Option<WorkerProperty>,
+}
+
+///redraw the bistability curve if the parameters changed, dropping it if closed by the user,
+///the steady states are sampled in the background only if anything but the detuning changed
+fn sync_bistability(
+    bistability: &mut Option<Bistability>,
+    simulator: &Worker,
+) -> Result<Task<Message>> {
+    let Some(ref mut b) = bistability else {
+        return Ok(Task::none());
+    };
+    if !b.plot.is_open() {
+        *bistability = None;
+        return Ok(Task::none());
+    }
+    let property = simulator.get_property();
+    let key = SCurveKey::new(simulator.topology(), property);
+    let mut task = Task::none();
+    //one sampling at a time, the latest parameters are sampled after it finishes
+    if !b.sampling && b.curve.as_ref().map(SCurve::key) != Some(&key) {
+        b.sampling = true;
+        task = Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || SCurve::compute(key))
+                    .await
+                    .ok()
+            },
+            Message::SCurveSampled,
+        );
+    }
+    match b.curve {
+        Some(ref curve) if b.drawn.as_ref() != Some(property) => {
+            b.drawn = Some(property.clone());
+            let (curves, markers) = bistability_curves(curve, simulator.topology(), property);
+            b.plot.draw(&curves, &markers)?;
+        }
+        _ => b.plot.refresh()?,
+    }
+    Ok(task)
+}
+
+///redraw `plot` if the parameters changed since it was drawn, dropping it if closed by the user
+fn sync_plot(
+    plot: &mut Option<(CurvePlot, Option<WorkerProperty>)>,
+    simulator: &Worker,
+    curves: fn(Topology, &WorkerProperty) -> PlotData,
+) -> Result<()> {
+    let Some((ref mut p, ref mut drawn)) = plot else {
+        return Ok(());
+    };
+    if !p.is_open() {
+        *plot = None;
+        return Ok(());
+    }
+    let property = simulator.get_property();
+    if drawn.as_ref() == Some(property) {
+        return p.refresh();
+    }
+    *drawn = Some(property.clone());
+    let (curves, markers) = curves(simulator.topology(), property);
+    p.draw(&curves, &markers)
+}
+
+fn init_from_property(p: WorkerUpdate) -> Control<f64> {
+    use WorkerUpdate::*;
+    match p {
+        Alpha(c, v) => Control::new(p, format!("Alpha {}", c.index() + 1), v.into()),
+        Pump(c, v) => Control::new(p, format!("Pump {}", c.index() + 1), v.into()),
+        PumpPhase(c, v) => Control::new(p, format!("Pump Phase {}", c.index() + 1), v.into()),
+        PumpParameter(c, k, v) => Control::new(
+            p,
+            format!("Pump {:?} {}", k, c.index() + 1),
+            match k {
+                lle_simulator::PumpParameter::Count | lle_simulator::PumpParameter::Harmonic => {
+                    None
+                }
+                _ => v.into(),
+            },
+        ),
+        DriveAmplitude(c, m, v) => Control::new(
+            p,
+            format!("Drive Amplitude {} at {}", c.index() + 1, m),
+            v.into(),
+        ),
+        DriveOffset(c, m, v) => Control::new(
+            p,
+            format!("Drive Offset {} at {}", c.index() + 1, m),
+            v.into(),
+        ),
+        Drift(c, v) => Control::new(p, format!("Drift {}", c.index() + 1), v.into()),
+        Linear(c, v) => Control::new(p, format!("Linear {}", c.index() + 1), v.into()),
+        Dispersion(c, order, v) => Control::new(
+            p,
+            format!("Order {} Dispersion {}", order, c.index() + 1),
+            v.into(),
+        ),
+        Loss(c, v) => Control::new(p, format!("Loss {}", c.index() + 1), v.into()),
+        LossParameter(c, k, v) => {
+            Control::new(p, format!("Loss {:?} {}", k, c.index() + 1), v.into())
+        }
+        RamanFraction(c, v) => {
+            Control::new(p, format!("Raman Fraction {}", c.index() + 1), v.into())
+        }
+        RamanTime(c, v) => Control::new(p, format!("Raman Time {}", c.index() + 1), v.into()),
+        ShockTime(c, v) => Control::new(p, format!("Shock Time {}", c.index() + 1), v.into()),
+        ThermalTime(c, _) => Control::new(p, format!("Thermal Time {}", c.index() + 1), None),
+        ThermalCoefficient(c, v) => Control::new(
+            p,
+            format!("Thermal Coefficient {}", c.index() + 1),
+            v.into(),
+        ),
+        RecordStep(_) => Control::new(p, "Record Step", None),
+        SimuStep(_) => Control::new(p, "Simulation Step", None),
+        Couple(m, v) => Control::new(p, format!("Couple Coefficient {}", m), v.into()),
+        CouplePhase(m, v) => Control::new(p, format!("Couple Phase {}", m), v.into()),
+        Xpm(v) => Control::new(p, "XPM Coefficient", v.into()),
+        Modes(_) => Control::new(p, "Modes", None),
+        Noise(_) => Control::new(p, "Noise Amplitude", None),
+    }
+}
+
+impl LleSimulator {
+    fn new(simulator: Worker) -> Self {
+        info!("random seed {}", simulator.get_property().seed);
+        let proper = simulator.get_property();
         Self {
-            draw1: DrawData::new(simulator.get_state().0.len(), (WIDTH, HEIGHT)),
-            draw2: DrawData::new(simulator.get_state().1.len(), (WIDTH, HEIGHT)),
+            draws: simulator
+                .get_state()
+                .into_iter()
+                .map(|s| DrawData::new(s.len(), WINDOW_SIZE))
+                .collect(),
+            panel: from_property_list(proper)
+                .into_iter()
+                .map(init_from_property)
+                .collect(),
+            seed: proper.seed.to_string(),
             simulator,
-            panel: array_init::from_iter(
-                IntoIterator::into_iter(from_property_array(proper)).map(|x| init_from_property(x)),
-            )
-            .expect("initializing state"),
+            dispersion_order: Default::default(),
+            drive_mode: Default::default(),
+            dint_path: Default::default(),
+            loss_table_path: Default::default(),
+            couple_mode: String::new(),
+            scan_form: ScanForm::default(),
+            scan_record_path: String::new(),
+            snapshot_path: String::new(),
+            init_kind: InitKind::Noise,
+            init_positions: String::new(),
+            bistability: None,
+            mi_gain: None,
             pause: true,
             last_update: None,
         }
     }
-}
 
-impl LleSimulator {
+    ///rebuild the display data if the mode number or topology of the simulator changed
+    fn sync_draw(&mut self) {
+        let states = self.simulator.get_state();
+        if self.draws.len() != states.len() {
+            self.draws = states
+                .into_iter()
+                .map(|s| DrawData::new(s.len(), WINDOW_SIZE))
+                .collect();
+            return;
+        }
+        for (d, s) in self.draws.iter_mut().zip(states) {
+            if d.data_len() != s.len() {
+                *d = DrawData::new(s.len(), WINDOW_SIZE);
+            }
+        }
+    }
+
+    ///keep one control for each property, reusing the existing ones to preserve slider ranges
+    fn sync_panel(&mut self) {
+        let mut old = std::mem::take(&mut self.panel);
+        self.panel = from_property_list(self.simulator.get_property())
+            .into_iter()
+            .map(|p| {
+                old.iter()
+                    .position(|c| same_property(c.property(), p))
+                    .map_or_else(|| init_from_property(p), |i| old.swap_remove(i))
+            })
+            .collect();
+    }
+
+    fn control_mut(&mut self, p: WorkerUpdate) -> Option<&mut Control> {
+        self.panel
+            .iter_mut()
+            .find(|c| same_property(c.property(), p))
+    }
+
     fn title(&self) -> String {
         "Lle Simulator".into()
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
+        let task = self.handle(message);
+        Task::batch([task, self.sync_analysis()])
+    }
+
+    ///redraw the analysis plots if the parameters changed, dropping those closed by the user
+    fn sync_analysis(&mut self) -> Task<Message> {
+        let task = sync_bistability(&mut self.bistability, &self.simulator).unwrap_or_else(|e| {
+            error!("drawing bistability curve: {:#}", e);
+            Task::none()
+        });
+        if let Err(e) = sync_plot(&mut self.mi_gain, &self.simulator, mi_curves) {
+            error!("drawing modulation instability gain: {:#}", e);
+        }
+        task
+    }
+
+    fn handle(&mut self, message: Message) -> Task<Message> {
         log::info!("update message: {:?}", message);
         match message {
             Message::Input(v) => {
                 v.apply_or_warn(|v| self.simulator.set_property(v));
+                self.sync_draw();
             }
             Message::Slide((v, t)) => match t {
                 SlideMessage::SetMax => {
                     v.apply_or_warn(|x| {
-                        self.panel[map_property_to_idx(x)].range_mut().map_or_else(
+                        self.control_mut(x).and_then(|c| c.range_mut()).map_or_else(
                             || warn!("none slider panel returned SetMax message"),
                             |r| r.higher = extract_property_value(x),
                         )
@@ -91,7 +324,7 @@ impl LleSimulator {
                 }
                 SlideMessage::SetMin => {
                     v.apply_or_warn(|x| {
-                        self.panel[map_property_to_idx(x)].range_mut().map_or_else(
+                        self.control_mut(x).and_then(|c| c.range_mut()).map_or_else(
                             || warn!("none slider panel returned SetMin message"),
                             |r| r.lower = extract_property_value(x),
                         )
@@ -99,15 +332,21 @@ impl LleSimulator {
                 }
                 SlideMessage::SetVal => {
                     v.apply_or_warn(|v| self.simulator.set_property(v));
+                    self.sync_draw();
                 }
             },
             Message::Tick => {
                 self.simulator.tick();
-                let state = self.simulator.get_state();
-                self.draw1.push(state.0.to_owned());
-                self.draw1.update().expect("refreshing status 1");
-                self.draw2.push(state.1.to_owned());
-                self.draw2.update().expect("refreshing status 2");
+                for (i, (d, s)) in self
+                    .draws
+                    .iter_mut()
+                    .zip(self.simulator.get_state())
+                    .enumerate()
+                {
+                    d.push(s.to_owned());
+                    d.update()
+                        .unwrap_or_else(|e| panic!("refreshing status {}: {}", i + 1, e));
+                }
                 if !self.pause {
                     const FPS: u64 = 60;
                     let duration: Duration = Duration::from_secs_f32(1. / FPS as f32);
@@ -132,6 +371,206 @@ impl LleSimulator {
                     return Task::perform(async {}, |_| Message::Tick);
                 }
             }
+            Message::DispersionOrder(c, s) => self.dispersion_order[c.index()] = s,
+            Message::AddDispersion(c) => match self.dispersion_order[c.index()].parse() {
+                Ok(order) => {
+                    if self.simulator.get_property().components[c.index()]
+                        .dispersion
+                        .contains_key(&order)
+                    {
+                        warn!("dispersion order {} already enabled", order);
+                    } else {
+                        self.simulator
+                            .set_property(WorkerUpdate::Dispersion(c, order, 0.));
+                        self.sync_panel();
+                    }
+                }
+                Err(_) => warn!("illegal order {}", self.dispersion_order[c.index()]),
+            },
+            Message::RemoveDispersion(c) => match self.dispersion_order[c.index()].parse() {
+                Ok(order) => {
+                    self.simulator.remove_dispersion(c, order);
+                    self.sync_panel();
+                }
+                Err(_) => warn!("illegal order {}", self.dispersion_order[c.index()]),
+            },
+            Message::DintPath(c, s) => self.dint_path[c.index()] = s,
+            Message::LoadDint(c) => {
+                if let Err(e) = ModeTable::from_csv(&self.dint_path[c.index()])
+                    .and_then(|t| self.simulator.set_dint(c, Some(t)))
+                {
+                    error!("loading D_int of component {}: {:#}", c.index() + 1, e);
+                }
+            }
+            Message::ClearDint(c) => self
+                .simulator
+                .set_dint(c, None)
+                .expect("clearing D_int table"),
+            Message::LossProfileKind(c, k) => {
+                self.simulator
+                    .set_loss_profile(c, k.default_profile())
+                    .expect("setting analytical loss profile");
+                self.sync_panel();
+            }
+            Message::LossTablePath(c, s) => self.loss_table_path[c.index()] = s,
+            Message::LoadLossTable(c) => {
+                match ModeTable::from_csv(&self.loss_table_path[c.index()])
+                    .and_then(|t| self.simulator.set_loss_profile(c, LossProfile::Table(t)))
+                {
+                    Ok(_) => self.sync_panel(),
+                    Err(e) => error!("loading loss table of component {}: {:#}", c.index() + 1, e),
+                }
+            }
+            Message::CoupleMode(s) => self.couple_mode = s,
+            Message::AddCoupling => match self.couple_mode.parse() {
+                Ok(mode) => {
+                    if self.simulator.get_property().couplings.contains_key(&mode) {
+                        warn!("coupling at mode {} already exists", mode);
+                    } else {
+                        self.simulator.set_property(WorkerUpdate::Couple(mode, 0.));
+                        self.sync_panel();
+                    }
+                }
+                Err(_) => warn!("illegal mode {}", self.couple_mode),
+            },
+            Message::RemoveCoupling => match self.couple_mode.parse() {
+                Ok(mode) => {
+                    self.simulator.remove_coupling(mode);
+                    self.sync_panel();
+                }
+                Err(_) => warn!("illegal mode {}", self.couple_mode),
+            },
+            Message::XpmPreset(x) => self
+                .simulator
+                .set_property(WorkerUpdate::Xpm(x.coefficient())),
+            Message::PumpShapeKind(c, k) => {
+                self.simulator.set_pump_shape(c, k.default_shape());
+                self.sync_panel();
+            }
+            Message::DriveMode(c, s) => self.drive_mode[c.index()] = s,
+            Message::AddDrive(c) => match self.drive_mode[c.index()].parse() {
+                Ok(mode) => {
+                    if self.simulator.get_property().components[c.index()]
+                        .drives
+                        .contains_key(&mode)
+                    {
+                        warn!("drive at mode {} already exists", mode);
+                    } else {
+                        self.simulator
+                            .set_property(WorkerUpdate::DriveAmplitude(c, mode, 0.));
+                        self.sync_panel();
+                    }
+                }
+                Err(_) => warn!("illegal mode {}", self.drive_mode[c.index()]),
+            },
+            Message::RemoveDrive(c) => match self.drive_mode[c.index()].parse() {
+                Ok(mode) => {
+                    self.simulator.remove_drive(c, mode);
+                    self.sync_panel();
+                }
+                Err(_) => warn!("illegal mode {}", self.drive_mode[c.index()]),
+            },
+            Message::PumpEnvelopeKind(c, k) => {
+                self.simulator.set_pump_envelope(c, k.default_envelope());
+                self.sync_panel();
+            }
+            Message::ToggleRaman(c, enable) => {
+                self.simulator.set_raman(c, enable.then(Raman::default));
+                self.sync_panel();
+            }
+            Message::ToggleThermal(c, enable) => {
+                self.simulator.set_thermal(c, enable.then(Thermal::default));
+                self.sync_panel();
+            }
+            Message::NoiseKind(k) => {
+                self.simulator.set_noise(k.default_model());
+                self.sync_panel();
+            }
+            Message::InitKind(k) => self.init_kind = k,
+            Message::InitPositions(s) => self.init_positions = s,
+            Message::Reset => {
+                if let Err(e) = self
+                    .init_kind
+                    .initial_condition(&self.init_positions)
+                    .and_then(|i| self.simulator.reset(&i))
+                {
+                    error!("resetting the fields: {:#}", e);
+                }
+            }
+            Message::SeedInput(s) => self.seed = s,
+            Message::SnapshotPath(s) => self.snapshot_path = s,
+            Message::SaveSnapshot => {
+                if let Err(e) = self.simulator.save(&self.snapshot_path) {
+                    error!("saving snapshot: {:#}", e);
+                }
+            }
+            Message::LoadSnapshot => match Worker::load(&self.snapshot_path) {
+                Ok(w) => {
+                    self.simulator = w;
+                    self.seed = self.simulator.get_property().seed.to_string();
+                    self.sync_draw();
+                    self.sync_panel();
+                }
+                Err(e) => error!("loading snapshot: {:#}", e),
+            },
+            Message::ToggleBistability(x) => {
+                self.bistability = x.then(|| Bistability {
+                    plot: CurvePlot::new("Bistability Curve", "Alpha 1", "Power 1", WINDOW_SIZE),
+                    curve: None,
+                    sampling: false,
+                    drawn: None,
+                })
+            }
+            Message::SCurveSampled(curve) => {
+                if let Some(ref mut b) = self.bistability {
+                    b.sampling = false;
+                    match curve {
+                        Some(c) => {
+                            b.curve = Some(c);
+                            b.drawn = None;
+                        }
+                        None => {
+                            error!("sampling bistability curve failed");
+                            self.bistability = None;
+                        }
+                    }
+                }
+            }
+            Message::ToggleMiGain(x) => {
+                self.mi_gain = x.then(|| {
+                    (
+                        CurvePlot::new("Modulation Instability Gain", "Mode", "Gain", WINDOW_SIZE),
+                        None,
+                    )
+                })
+            }
+            Message::Restart => match self.seed.trim().parse() {
+                Ok(seed) => {
+                    self.simulator.restart(seed);
+                    self.sync_draw();
+                }
+                Err(_) => warn!("illegal seed {}", self.seed),
+            },
+            Message::ScanInput(f, s) => self.scan_form.fields[f.index()] = s,
+            Message::StartScan => {
+                if let Err(e) = self
+                    .scan_form
+                    .protocol()
+                    .and_then(|p| self.simulator.start_scan(p))
+                {
+                    error!("starting detuning scan: {:#}", e);
+                }
+            }
+            Message::StopScan => self.simulator.stop_scan(),
+            Message::ScanRecordPath(s) => self.scan_record_path = s,
+            Message::SaveScanRecord => match self.simulator.scan() {
+                Some(s) => {
+                    if let Err(e) = s.save_csv(&self.scan_record_path) {
+                        error!("saving scan record: {:#}", e);
+                    }
+                }
+                None => warn!("no detuning scan recorded"),
+            },
         };
         Task::none()
     }
@@ -140,18 +579,247 @@ impl LleSimulator {
         let mut control = column![]
             .spacing(20)
             .align_x(Alignment::Center)
-            .width(Length::Fill)
-            .height(Length::Fill);
+            .width(Length::Fill);
 
         let proper = self.simulator.get_property();
-        for (c, w) in self
-            .panel
-            .iter()
-            .zip(IntoIterator::into_iter(from_property_array(proper)))
-        {
+        let single = self.simulator.topology() == Topology::Single;
+        for (c, w) in self.panel.iter().zip(from_property_list(proper)) {
+            if single
+                && (matches!(
+                    w,
+                    WorkerUpdate::Couple(..) | WorkerUpdate::CouplePhase(..) | WorkerUpdate::Xpm(_)
+                ) || property_component(w) == Some(Component::Second))
+            {
+                continue;
+            }
             control = control.push(c.view(w));
         }
 
+        for c in Component::ALL {
+            if single && c == Component::Second {
+                continue;
+            }
+            control = control.push(
+                row![
+                    text(format!("Pump Shape {}", c.index() + 1)),
+                    pick_list(
+                        ShapeKind::ALL,
+                        Some(ShapeKind::of(&proper.components[c.index()].pump_shape)),
+                        move |k| Message::PumpShapeKind(c, k),
+                    ),
+                    text(format!("Pump Envelope {}", c.index() + 1)),
+                    pick_list(
+                        EnvelopeKind::ALL,
+                        Some(EnvelopeKind::of(
+                            &proper.components[c.index()].pump_envelope
+                        )),
+                        move |k| Message::PumpEnvelopeKind(c, k),
+                    ),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            );
+            control = control.push(
+                row![
+                    text(format!("Drive Mode {}", c.index() + 1)),
+                    text_input("mode", &self.drive_mode[c.index()])
+                        .on_input(move |x| Message::DriveMode(c, x)),
+                    button(text("Add")).on_press(Message::AddDrive(c)),
+                    button(text("Remove")).on_press(Message::RemoveDrive(c)),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            );
+            control = control.push(
+                row![
+                    text(format!("Dispersion Order {}", c.index() + 1)),
+                    text_input("order", &self.dispersion_order[c.index()])
+                        .on_input(move |x| Message::DispersionOrder(c, x)),
+                    button(text("Add")).on_press(Message::AddDispersion(c)),
+                    button(text("Remove")).on_press(Message::RemoveDispersion(c)),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            );
+            let loaded = self.simulator.get_property().components[c.index()]
+                .dint
+                .as_ref()
+                .map_or("none".to_string(), |t| format!("{} modes", t.len()));
+            control = control.push(
+                row![
+                    text(format!("D_int Table {} ({})", c.index() + 1, loaded)),
+                    text_input("csv path", &self.dint_path[c.index()])
+                        .on_input(move |x| Message::DintPath(c, x)),
+                    button(text("Load")).on_press(Message::LoadDint(c)),
+                    button(text("Clear")).on_press(Message::ClearDint(c)),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            );
+            control = control.push(
+                row![
+                    text(format!("Loss Profile {}", c.index() + 1)),
+                    pick_list(
+                        ProfileKind::ALL,
+                        ProfileKind::of(
+                            &self.simulator.get_property().components[c.index()].loss_profile
+                        ),
+                        move |k| Message::LossProfileKind(c, k),
+                    ),
+                    text_input("csv path", &self.loss_table_path[c.index()])
+                        .on_input(move |x| Message::LossTablePath(c, x)),
+                    button(text("Load Table")).on_press(Message::LoadLossTable(c)),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            );
+            control = control.push(
+                checkbox(
+                    format!("Raman Response {}", c.index() + 1),
+                    self.simulator.get_property().components[c.index()]
+                        .raman
+                        .is_some(),
+                )
+                .on_toggle(move |x| Message::ToggleRaman(c, x)),
+            );
+            let thermal = self.simulator.get_property().components[c.index()]
+                .thermal
+                .is_some();
+            let mut thermal_row =
+                row![
+                    checkbox(format!("Thermal Effect {}", c.index() + 1), thermal)
+                        .on_toggle(move |x| Message::ToggleThermal(c, x))
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center);
+            if thermal {
+                thermal_row = thermal_row.push(text(format!(
+                    "Temperature {:.4}, Effective Alpha {:.4}",
+                    self.simulator.temperature(c),
+                    self.simulator.get_property().components[c.index()].alpha
+                        + self.simulator.detuning_shift(c)
+                )));
+            }
+            control = control.push(thermal_row);
+        }
+
+        if !single {
+            control = control.push(
+                row![
+                    text("Coupled Mode"),
+                    text_input("mode", &self.couple_mode).on_input(Message::CoupleMode),
+                    button(text("Add")).on_press(Message::AddCoupling),
+                    button(text("Remove")).on_press(Message::RemoveCoupling),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            );
+            control = control.push(
+                row![
+                    text("XPM Preset"),
+                    pick_list(
+                        XpmPreset::ALL,
+                        XpmPreset::of(self.simulator.get_property().xpm),
+                        Message::XpmPreset,
+                    ),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        control = control.push(
+            row![
+                text("Noise Model"),
+                pick_list(
+                    NoiseKind::ALL,
+                    Some(NoiseKind::of(&self.simulator.get_property().noise)),
+                    Message::NoiseKind,
+                ),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+        );
+        control = control.push(
+            row![
+                text("Initial Condition"),
+                pick_list(InitKind::ALL, Some(self.init_kind), Message::InitKind),
+                text_input("position, spacings", &self.init_positions)
+                    .on_input(Message::InitPositions),
+                button(text("Reset")).on_press(Message::Reset),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+        );
+        control = control.push(
+            row![
+                checkbox("Bistability Curve", self.bistability.is_some())
+                    .on_toggle(Message::ToggleBistability),
+                checkbox("MI Gain", self.mi_gain.is_some()).on_toggle(Message::ToggleMiGain),
+            ]
+            .spacing(20),
+        );
+        control = control.push(
+            row![
+                text("Seed"),
+                text_input("seed", &self.seed).on_input(Message::SeedInput),
+                button(text("Restart")).on_press(Message::Restart),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+        );
+
+        control = control.push(
+            row![
+                text("Snapshot"),
+                text_input("json path", &self.snapshot_path).on_input(Message::SnapshotPath),
+                button(text("Save")).on_press(Message::SaveSnapshot),
+                button(text("Load")).on_press(Message::LoadSnapshot),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+        );
+
+        let scan_inputs = ScanField::ALL.iter().fold(
+            row![text("Detuning Scan")]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            |r, &f| {
+                r.push(
+                    text_input(f.placeholder(), &self.scan_form.fields[f.index()])
+                        .on_input(move |x| Message::ScanInput(f, x)),
+                )
+            },
+        );
+        control = control.push(
+            scan_inputs
+                .push(button(text("Start")).on_press(Message::StartScan))
+                .push(button(text("Stop")).on_press(Message::StopScan)),
+        );
+        let scan_status = match self.simulator.scan() {
+            None => "no scan".to_string(),
+            Some(s) => format!(
+                "{} at time {:.3}/{:.3}, {} points recorded",
+                if s.is_running() {
+                    "scanning"
+                } else {
+                    "stopped"
+                },
+                s.time(),
+                s.protocol().duration(),
+                s.record().len()
+            ),
+        };
+        control = control.push(
+            row![
+                text(scan_status),
+                text_input("csv path", &self.scan_record_path).on_input(Message::ScanRecordPath),
+                button(text("Save Record")).on_press(Message::SaveScanRecord),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+        );
+
         let pause_button = button(text(if self.pause { "Run" } else { "Pause" }))
             .on_press(Message::Pause)
             .padding(10);
@@ -167,7 +835,7 @@ impl LleSimulator {
             .width(Length::Shrink),
         );
 
-        container(control)
+        container(scrollable(control))
             .width(Length::Fill)
             .height(Length::Fill)
             .padding(5)
@@ -0,0 +1,105 @@
+use lle::{num_complex::Complex64, Freq, LinearOp};
+
+use crate::{
+    linear::ComponentLinear,
+    mode_range,
+    steady::{eigenvalues, jacobian},
+    CwState, WorkerProperty,
+};
+
+///modulation instability gain of the homogeneous `state`, the largest growth rate of the
+///perturbations at each pair of modes `±μ` in the simulated range, indexed by `μ`,
+///the raman response, self-steepening and thermal shift are neglected
+pub fn mi_gain(property: &WorkerProperty, state: &CwState) -> Vec<(Freq, f64)> {
+    let linear: Vec<ComponentLinear> = property.components[..state.fields.len()]
+        .iter()
+        .map(ComponentLinear::new)
+        .collect();
+    let coupled = state.fields.len() > 1;
+    let (lower, upper) = mode_range(property.modes);
+    (lower..=upper)
+        .map(|mu| {
+            let l: Vec<(Complex64, Complex64)> = linear
+                .iter()
+                .map(|l| (l.get_value(0, mu), l.get_value(0, -mu)))
+                .collect();
+            let coupling = if coupled {
+                [
+                    property.couplings.get(&mu).copied(),
+                    property.couplings.get(&-mu).copied(),
+                ]
+            } else {
+                [None; 2]
+            };
+            let gain = eigenvalues(&jacobian(&l, coupling, property.xpm, &state.fields))
+                .into_iter()
+                .map(|x| x.re)
+                .fold(f64::NEG_INFINITY, f64::max);
+            (mu, gain)
+        })
+        .collect()
+}
+
+///the fastest growing mode `μ > 0` with its gain, predicting `μ` rolls in the round trip,
+///`None` if no sideband grows
+pub fn roll_number(gain: &[(Freq, f64)]) -> Option<(Freq, f64)> {
+    gain.iter()
+        .filter(|&&(m, g)| m > 0 && g > 0.)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cw_states, Topology};
+
+    #[test]
+    fn single_resonator_gain() {
+        let mut property = WorkerProperty::new(128, 0);
+        let p = &mut property.components[0];
+        p.alpha = 1.;
+        p.pump = 2.;
+        p.drift = 0.3;
+        let (alpha, loss, d2) = (p.alpha, p.loss, p.linear);
+        let states = cw_states(Topology::Single, &property);
+        assert!(!states.is_empty());
+        for state in states {
+            let rho = state.powers()[0];
+            let gain = mi_gain(&property, &state);
+            assert_eq!(gain.len(), property.modes);
+            for &(mu, g) in gain.iter() {
+                let detuning = alpha - 2. * rho - d2 * (mu * mu) as f64 / 2.;
+                let expected = -loss + (rho * rho - detuning * detuning).max(0.).sqrt();
+                assert!(
+                    (g - expected).abs() < 1e-9,
+                    "mode {}: {} vs {}",
+                    mu,
+                    g,
+                    expected
+                );
+            }
+            let analytic = (1..=mode_range(property.modes).1)
+                .map(|mu| {
+                    let detuning = alpha - 2. * rho - d2 * (mu * mu) as f64 / 2.;
+                    (mu, -loss + (rho * rho - detuning * detuning).max(0.).sqrt())
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .filter(|&(_, g)| g > 0.);
+            assert_eq!(roll_number(&gain).map(|(m, _)| m), analytic.map(|(m, _)| m));
+        }
+    }
+
+    #[test]
+    fn roll_number_of_gain() {
+        assert_eq!(
+            roll_number(&[(-2, 3.), (-1, -1.), (0, 0.5), (1, -0.2)]),
+            None
+        );
+        assert_eq!(
+            roll_number(&[(-3, 5.), (0, 4.), (1, 0.1), (2, 0.7), (3, 0.4)]),
+            Some((2, 0.7))
+        );
+        assert_eq!(roll_number(&[]), None);
+    }
+}
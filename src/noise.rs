@@ -0,0 +1,167 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use lle::{num_complex::Complex64, Freq};
+use rand::Rng;
+use rustfft::{Fft, FftPlanner};
+use serde::{Deserialize, Serialize};
+
+use crate::ComponentProperty;
+
+///noise injected into every mode while evolving, as a white Langevin force whose
+///increments over a step `dt` have a variance proportional to `dt`,
+///defaults to vacuum noise so that modulation instability grows from the cold cavity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum NoiseModel {
+    None,
+    ///vacuum fluctuations entering through the loss, keeping one photon per mode in the cold
+    ///cavity, `photon` is the normalized amplitude of a single intracavity photon
+    Vacuum {
+        photon: f64,
+    },
+    ///white noise of `amplitude² * dt` mean power per mode over a step `dt`
+    White {
+        amplitude: f64,
+    },
+}
+
+impl Default for NoiseModel {
+    fn default() -> Self {
+        NoiseModel::Vacuum { photon: 1e-4 }
+    }
+}
+
+impl NoiseModel {
+    ///mean power per unit time injected into each mode of a component with `loss`
+    pub fn power_density(&self, loss: f64) -> f64 {
+        match *self {
+            NoiseModel::None => 0.,
+            //the field decays at `loss`, its power at twice of it
+            NoiseModel::Vacuum { photon } => 2. * loss * photon * photon,
+            NoiseModel::White { amplitude } => amplitude * amplitude,
+        }
+    }
+    pub fn amplitude(&self) -> Option<f64> {
+        match *self {
+            NoiseModel::None => None,
+            NoiseModel::Vacuum { photon } => Some(photon),
+            NoiseModel::White { amplitude } => Some(amplitude),
+        }
+    }
+    ///set the amplitude of the model, returning `false` for [`NoiseModel::None`]
+    pub fn set_amplitude(&mut self, value: f64) -> bool {
+        match self {
+            NoiseModel::None => return false,
+            NoiseModel::Vacuum { photon } => *photon = value,
+            NoiseModel::White { amplitude } => *amplitude = value,
+        }
+        true
+    }
+    ///add the noise accumulated over `duration` to the time domain `state` of a component,
+    ///each mode draws independent noise following its own loss including the loss profile
+    pub(crate) fn apply(
+        &self,
+        source: &mut NoiseSource,
+        property: &ComponentProperty,
+        state: &mut [Complex64],
+        duration: f64,
+        rng: &mut impl Rng,
+    ) {
+        if *self == NoiseModel::None {
+            return;
+        }
+        let len = state.len();
+        if source.buffer.len() != len {
+            *source = NoiseSource::new(len);
+        }
+        let (lower, upper) = crate::mode_range(len);
+        for mode in lower..=upper {
+            let loss = property.loss + property.loss_profile.value(mode);
+            let sigma = (self.power_density(loss) * duration).sqrt();
+            source.buffer[mode.rem_euclid(len as Freq) as usize] = sigma * complex_gaussian(rng);
+        }
+        //the unnormalized inverse transform sums the modes into each sample
+        source.inverse.process(&mut source.buffer);
+        state
+            .iter_mut()
+            .zip(source.buffer.iter())
+            .for_each(|(x, n)| *x += n);
+    }
+}
+
+///planned inverse fft and buffer bringing the noise of each mode to the time domain
+pub(crate) struct NoiseSource {
+    inverse: Arc<dyn Fft<f64>>,
+    buffer: Vec<Complex64>,
+}
+
+impl NoiseSource {
+    pub fn new(len: usize) -> Self {
+        Self {
+            inverse: FftPlanner::new().plan_fft_inverse(len),
+            buffer: vec![Complex64::default(); len],
+        }
+    }
+}
+
+///circular complex gaussian of unit mean power, by the Box-Muller transform
+fn complex_gaussian(rng: &mut impl Rng) -> Complex64 {
+    let r = (-(1. - rng.random::<f64>()).ln()).sqrt();
+    Complex64::from_polar(r, 2. * PI * rng.random::<f64>())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{LossProfile, WorkerProperty};
+
+    #[test]
+    fn vacuum_follows_mode_loss() {
+        let len = 16;
+        let mut property = WorkerProperty::new(len, 0).components[0].clone();
+        property.loss_profile = LossProfile::RollOff { curvature: 0.1 };
+        let noise = NoiseModel::Vacuum { photon: 1. };
+        let (mut source, mut rng) = (NoiseSource::new(len), StdRng::seed_from_u64(7));
+        let forward = FftPlanner::new().plan_fft_forward(len);
+        let (draws, duration) = (4000, 1e-2);
+        let mut power = vec![0.; len];
+        for _ in 0..draws {
+            let mut state = vec![Complex64::default(); len];
+            noise.apply(&mut source, &property, &mut state, duration, &mut rng);
+            forward.process(&mut state);
+            power
+                .iter_mut()
+                .zip(state.iter())
+                .for_each(|(p, x)| *p += (x / len as f64).norm_sqr() / draws as f64);
+        }
+        let (lower, upper) = crate::mode_range(len);
+        for mode in lower..=upper {
+            let loss = property.loss + property.loss_profile.value(mode);
+            let expected = noise.power_density(loss) * duration;
+            let measured = power[mode.rem_euclid(len as Freq) as usize];
+            assert!(
+                (measured / expected - 1.).abs() < 0.1,
+                "mode {}: {} vs {}",
+                mode,
+                measured,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn none_adds_nothing() {
+        let property = WorkerProperty::new(8, 0).components[0].clone();
+        let mut state = vec![Complex64::new(1., 0.); 8];
+        NoiseModel::None.apply(
+            &mut NoiseSource::new(8),
+            &property,
+            &mut state,
+            1.,
+            &mut StdRng::seed_from_u64(0),
+        );
+        assert!(state.iter().all(|&x| x == Complex64::new(1., 0.)));
+    }
+}
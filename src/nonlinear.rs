@@ -0,0 +1,241 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use lle::{num_complex::Complex64, Freq, NonLinearOp, Step};
+use rustfft::{Fft, FftPlanner};
+use serde::{Deserialize, Serialize};
+
+use crate::ComponentProperty;
+
+///delayed raman response with an exponential decaying kernel
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Raman {
+    ///fractional contribution of the delayed response to the nonlinearity
+    pub fraction: f64,
+    ///response time in the normalized fast time, the round trip spanning `2π`
+    pub time: f64,
+}
+
+impl Default for Raman {
+    fn default() -> Self {
+        Self {
+            fraction: 0.18,
+            time: 0.02,
+        }
+    }
+}
+
+///kerr nonlinearity of one component, `i * P` with
+///`P = (1 - f_R) * |A|² + f_R * h ⊛ |A|²` and the raman response `h` if enabled,
+///the self-steepening term `-τ_s * ∂(P * A) / ∂θ / A` added if the shock time `τ_s` isn't 0
+#[derive(Clone, Default)]
+pub struct Kerr {
+    raman: Option<Raman>,
+    shock_time: f64,
+    intensity: Vec<f64>,
+    delayed: Vec<f64>,
+    shock: Option<Shock>,
+}
+
+impl Kerr {
+    pub fn new(property: &ComponentProperty) -> Self {
+        Self {
+            raman: property.raman,
+            shock_time: property.shock_time,
+            intensity: Vec::new(),
+            delayed: Vec::new(),
+            shock: None,
+        }
+    }
+}
+
+///planned ffts and buffer for the spectral derivative along the fast time
+#[derive(Clone)]
+struct Shock {
+    forward: Arc<dyn Fft<f64>>,
+    inverse: Arc<dyn Fft<f64>>,
+    buffer: Vec<Complex64>,
+}
+
+impl Shock {
+    fn new(len: usize) -> Self {
+        let mut planner = FftPlanner::new();
+        Self {
+            forward: planner.plan_fft_forward(len),
+            inverse: planner.plan_fft_inverse(len),
+            buffer: vec![Complex64::default(); len],
+        }
+    }
+    ///derivative of the periodic `buffer` over the round trip `[0, 2π)`, done in place
+    fn differentiate(&mut self) {
+        let len = self.buffer.len();
+        self.forward.process(&mut self.buffer);
+        let (lower, upper) = crate::mode_range(len);
+        for mode in lower..=upper {
+            //the unpaired nyquist mode has no well defined derivative
+            let factor = if -mode > upper {
+                Complex64::default()
+            } else {
+                Complex64::i() * mode as f64 / len as f64
+            };
+            self.buffer[mode.rem_euclid(len as Freq) as usize] *= factor;
+        }
+        self.inverse.process(&mut self.buffer);
+    }
+}
+
+///periodic convolution of `intensity` with the causal kernel `exp(-t / time) / time`,
+///done in place by a first order recursion
+fn delayed_response(intensity: &mut [f64], time: f64) {
+    let len = intensity.len();
+    let decay = (-2. * PI / len as f64 / time).exp();
+    //response accumulated over one round trip starting from zero
+    let last = intensity
+        .iter()
+        .fold(0., |r, &x| decay * r + (1. - decay) * x);
+    //the periodic solution starts from the fixed point of one round trip
+    let mut r = last / (1. - decay.powi(len as i32));
+    intensity.iter_mut().for_each(|x| {
+        r = decay * r + (1. - decay) * *x;
+        *x = r;
+    });
+}
+
+impl NonLinearOp<f64> for Kerr {
+    fn get_value(&mut self, _step: Step, state: &[Complex64], dst: &mut [Complex64]) {
+        self.intensity.clear();
+        self.intensity.extend(state.iter().map(|x| x.norm_sqr()));
+        if let Some(Raman { fraction, time }) = self.raman {
+            self.delayed.clone_from(&self.intensity);
+            delayed_response(&mut self.delayed, time);
+            self.intensity
+                .iter_mut()
+                .zip(self.delayed.iter())
+                .for_each(|(x, r)| *x = (1. - fraction) * *x + fraction * r);
+        }
+        dst.iter_mut()
+            .zip(self.intensity.iter())
+            .for_each(|(d, &p)| *d = Complex64::i() * p);
+        if self.shock_time == 0. {
+            return;
+        }
+        let shock = match self.shock {
+            Some(ref mut s) if s.buffer.len() == state.len() => s,
+            _ => self.shock.insert(Shock::new(state.len())),
+        };
+        shock
+            .buffer
+            .iter_mut()
+            .zip(state.iter().zip(self.intensity.iter()))
+            .for_each(|(b, (x, p))| *b = x * p);
+        shock.differentiate();
+        let shock_time = self.shock_time;
+        dst.iter_mut()
+            .zip(state.iter().zip(shock.buffer.iter()))
+            .for_each(|(d, (x, dp))| {
+                //the field vanishes only far below the noise floor, where the term is negligible
+                let norm = x.norm_sqr();
+                if norm > f64::MIN_POSITIVE {
+                    *d -= shock_time * dp * x.conj() / norm;
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::WorkerProperty;
+
+    use super::*;
+
+    #[test]
+    fn delayed_response_periodic() {
+        let len = 64;
+        let time = 0.3;
+        let intensity: Vec<f64> = (0..len)
+            .map(|i| 1. + (2. * PI * i as f64 / len as f64).sin().powi(3))
+            .collect();
+        let mut response = intensity.clone();
+        delayed_response(&mut response, time);
+        //one more step from the end of the round trip reproduces its start
+        let decay = (-2. * PI / len as f64 / time).exp();
+        let next = decay * response[len - 1] + (1. - decay) * intensity[0];
+        assert!((next - response[0]).abs() < 1e-12);
+        //the convolution over many round trips converges to the same response
+        let mut r = 0.;
+        for i in 0..len * 20 {
+            r = decay * r + (1. - decay) * intensity[i % len];
+        }
+        assert!((r - response[len - 1]).abs() < 1e-12);
+        //the normalized kernel keeps the average intensity
+        let average = |x: &[f64]| x.iter().sum::<f64>() / len as f64;
+        assert!((average(&response) - average(&intensity)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn delayed_response_constant() {
+        let mut response = vec![2.5; 32];
+        delayed_response(&mut response, 0.05);
+        assert!(response.iter().all(|x| (x - 2.5).abs() < 1e-12));
+    }
+
+    fn grid(len: usize) -> impl Iterator<Item = f64> {
+        (0..len).map(move |i| 2. * PI * i as f64 / len as f64)
+    }
+
+    #[test]
+    fn differentiate() {
+        let len = 16;
+        let mut shock = Shock::new(len);
+        for k in [1., 3., 7.] {
+            shock.buffer = grid(len)
+                .map(|t| Complex64::new((k * t).cos(), 0.))
+                .collect();
+            shock.differentiate();
+            for (x, t) in shock.buffer.iter().zip(grid(len)) {
+                assert!((x - Complex64::new(-k * (k * t).sin(), 0.)).norm() < 1e-12);
+            }
+        }
+        //the nyquist mode is dropped
+        shock.buffer = grid(len)
+            .map(|t| Complex64::new((8. * t).cos(), 0.))
+            .collect();
+        shock.differentiate();
+        assert!(shock.buffer.iter().all(|x| x.norm() < 1e-12));
+    }
+
+    fn kerr(shock_time: f64, state: &[Complex64]) -> Vec<Complex64> {
+        let property = ComponentProperty {
+            shock_time,
+            ..WorkerProperty::new(state.len(), 0).components[0].clone()
+        };
+        let mut dst = vec![Complex64::default(); state.len()];
+        Kerr::new(&property).get_value(0, state, &mut dst);
+        dst
+    }
+
+    #[test]
+    fn shock_term() {
+        let len = 32;
+        let shock_time = 0.01;
+        //a phase ramp of `k` turns has a flat intensity, the term `-i * τ_s * k` slows its phase
+        let k = 3.;
+        let ramp: Vec<_> = grid(len)
+            .map(|t| Complex64::from_polar(2., k * t))
+            .collect();
+        for d in kerr(shock_time, &ramp) {
+            assert!((d - Complex64::new(0., 4. * (1. - shock_time * k))).norm() < 1e-12);
+        }
+        //a real field `a` gives `-τ_s * ∂(a³) / ∂θ / a = -3 * τ_s * a * ∂a / ∂θ`
+        let a = |t: f64| 1. + 0.5 * t.cos();
+        let field: Vec<_> = grid(len)
+            .map(|t| Complex64::from_polar(a(t), 0.7))
+            .collect();
+        let plain = kerr(0., &field);
+        for ((d, p), t) in kerr(shock_time, &field).iter().zip(plain).zip(grid(len)) {
+            assert!((p - Complex64::new(0., a(t).powi(2))).norm() < 1e-12);
+            let expected = 3. * shock_time * a(t) * 0.5 * t.sin();
+            assert!((d - p - expected).norm() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::f64::consts::PI;
+
+use lle::{num_complex::Complex64, ConstOp, Step};
+use serde::{Deserialize, Serialize};
+
+use crate::{anyhow, finite, positive, ComponentProperty, Result};
+
+///profile of the pump in the fast time, multiplying the pump amplitude
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum PumpShape {
+    #[default]
+    Cw,
+    ///`count` evenly spaced sech pulses in a round trip, each of `width` in the fast time
+    PulseTrain { width: f64, count: u32 },
+    ///`(1 + a * cos(hθ)) * exp(i * p * cos(hθ))` with amplitude depth `a`, phase depth `p`
+    ///and `h` the harmonic of the round trip frequency
+    Modulated {
+        amplitude_depth: f64,
+        phase_depth: f64,
+        harmonic: u32,
+    },
+}
+
+///variation of the pump amplitude in the slow time, multiplying the pump amplitude
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum PumpEnvelope {
+    #[default]
+    Constant,
+    ///linear turn on from 0 to full amplitude over `duration`
+    Ramp { duration: f64 },
+    ///`1 + depth * sin(2π * t / period)`
+    Sine { depth: f64, period: f64 },
+}
+
+///scalar parameters of [`PumpShape`]s and [`PumpEnvelope`]s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PumpParameter {
+    Width,
+    Count,
+    AmplitudeDepth,
+    PhaseDepth,
+    Harmonic,
+    RampDuration,
+    SineDepth,
+    SinePeriod,
+}
+
+impl PumpShape {
+    ///value at the fast time `theta` in `[0, 2π)`
+    pub fn value(&self, theta: f64) -> Complex64 {
+        match *self {
+            PumpShape::Cw => Complex64::new(1., 0.),
+            PumpShape::PulseTrain { width, count } => {
+                let period = 2. * PI / count as f64;
+                //distance to the center of the nearest pulse
+                let offset = (theta % period) - period / 2.;
+                Complex64::new(1. / (offset / width).cosh(), 0.)
+            }
+            PumpShape::Modulated {
+                amplitude_depth,
+                phase_depth,
+                harmonic,
+            } => {
+                let m = (harmonic as f64 * theta).cos();
+                Complex64::from_polar(1. + amplitude_depth * m, phase_depth * m)
+            }
+        }
+    }
+    pub fn parameters(&self) -> Vec<(PumpParameter, f64)> {
+        match *self {
+            PumpShape::Cw => Vec::new(),
+            PumpShape::PulseTrain { width, count } => vec![
+                (PumpParameter::Width, width),
+                (PumpParameter::Count, count as f64),
+            ],
+            PumpShape::Modulated {
+                amplitude_depth,
+                phase_depth,
+                harmonic,
+            } => vec![
+                (PumpParameter::AmplitudeDepth, amplitude_depth),
+                (PumpParameter::PhaseDepth, phase_depth),
+                (PumpParameter::Harmonic, harmonic as f64),
+            ],
+        }
+    }
+    ///set a parameter of the shape, returning `false` if the shape doesn't have it,
+    ///integer parameters are rounded and at least 1,
+    ///the shape is kept unchanged if the value is invalid
+    pub fn set_parameter(&mut self, parameter: PumpParameter, value: f64) -> Result<bool> {
+        let mut shape = *self;
+        match (&mut shape, parameter) {
+            (PumpShape::PulseTrain { width, .. }, PumpParameter::Width) => *width = value,
+            (PumpShape::PulseTrain { count, .. }, PumpParameter::Count) => {
+                *count = value.round().max(1.) as u32
+            }
+            (
+                PumpShape::Modulated {
+                    amplitude_depth, ..
+                },
+                PumpParameter::AmplitudeDepth,
+            ) => *amplitude_depth = value,
+            (PumpShape::Modulated { phase_depth, .. }, PumpParameter::PhaseDepth) => {
+                *phase_depth = value
+            }
+            (PumpShape::Modulated { harmonic, .. }, PumpParameter::Harmonic) => {
+                *harmonic = value.round().max(1.) as u32
+            }
+            _ => return Ok(false),
+        }
+        shape.check()?;
+        *self = shape;
+        Ok(true)
+    }
+    pub fn check(&self) -> Result<()> {
+        match *self {
+            PumpShape::Cw => (),
+            PumpShape::PulseTrain { width, count } => {
+                positive("pulse width", width)?;
+                if count == 0 {
+                    return Err(anyhow!("pulse count should be positive"));
+                }
+            }
+            PumpShape::Modulated {
+                amplitude_depth,
+                phase_depth,
+                harmonic,
+            } => {
+                finite("amplitude modulation depth", amplitude_depth)?;
+                finite("phase modulation depth", phase_depth)?;
+                if harmonic == 0 {
+                    return Err(anyhow!("modulation harmonic should be positive"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PumpEnvelope {
+    ///value at the slow time `time`
+    pub fn value(&self, time: f64) -> f64 {
+        match *self {
+            PumpEnvelope::Constant => 1.,
+            PumpEnvelope::Ramp { duration } => (time / duration).clamp(0., 1.),
+            PumpEnvelope::Sine { depth, period } => 1. + depth * (2. * PI * time / period).sin(),
+        }
+    }
+    pub fn is_constant(&self) -> bool {
+        *self == PumpEnvelope::Constant
+    }
+    pub fn parameters(&self) -> Vec<(PumpParameter, f64)> {
+        match *self {
+            PumpEnvelope::Constant => Vec::new(),
+            PumpEnvelope::Ramp { duration } => vec![(PumpParameter::RampDuration, duration)],
+            PumpEnvelope::Sine { depth, period } => vec![
+                (PumpParameter::SineDepth, depth),
+                (PumpParameter::SinePeriod, period),
+            ],
+        }
+    }
+    ///set a parameter of the envelope, returning `false` if the envelope doesn't have it,
+    ///the envelope is kept unchanged if the value is invalid
+    pub fn set_parameter(&mut self, parameter: PumpParameter, value: f64) -> Result<bool> {
+        let mut envelope = *self;
+        match (&mut envelope, parameter) {
+            (PumpEnvelope::Ramp { duration }, PumpParameter::RampDuration) => *duration = value,
+            (PumpEnvelope::Sine { depth, .. }, PumpParameter::SineDepth) => *depth = value,
+            (PumpEnvelope::Sine { period, .. }, PumpParameter::SinePeriod) => *period = value,
+            _ => return Ok(false),
+        }
+        envelope.check()?;
+        *self = envelope;
+        Ok(true)
+    }
+    pub fn check(&self) -> Result<()> {
+        match *self {
+            PumpEnvelope::Constant => (),
+            PumpEnvelope::Ramp { duration } => {
+                positive("ramp duration", duration)?;
+            }
+            PumpEnvelope::Sine { depth, period } => {
+                finite("sine depth", depth)?;
+                positive("sine period", period)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+///time domain pump term of one component, the shape sampled on the simulation grid
+#[derive(Debug, Clone)]
+pub struct Pump {
+    ///complex amplitude with the slow time envelope applied
+    amplitude: Complex64,
+    ///`None` for a cw pump
+    shape: Option<Vec<Complex64>>,
+}
+
+impl Pump {
+    pub fn new(property: &ComponentProperty, modes: usize, time: f64) -> Self {
+        Self {
+            amplitude: Self::amplitude(property, time),
+            shape: match property.pump_shape {
+                PumpShape::Cw => None,
+                s => Some(
+                    (0..modes)
+                        .map(|i| s.value(2. * PI * i as f64 / modes as f64))
+                        .collect(),
+                ),
+            },
+        }
+    }
+    pub fn amplitude(property: &ComponentProperty, time: f64) -> Complex64 {
+        Complex64::from_polar(
+            property.pump * property.pump_envelope.value(time),
+            property.pump_phase,
+        )
+    }
+    pub fn set_amplitude(&mut self, amplitude: Complex64) {
+        self.amplitude = amplitude;
+    }
+}
+
+impl ConstOp<f64> for Pump {
+    fn get_value(&self, _cur_step: Step, pos: usize, _state: &[Complex64]) -> Complex64 {
+        match self.shape {
+            Some(ref s) => self.amplitude * s[pos],
+            None => self.amplitude,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_parameter_rejected() {
+        let mut shape = PumpShape::PulseTrain {
+            width: 0.1,
+            count: 2,
+        };
+        assert!(shape.set_parameter(PumpParameter::Width, 0.).is_err());
+        assert!(shape.set_parameter(PumpParameter::Width, f64::NAN).is_err());
+        assert_eq!(
+            shape,
+            PumpShape::PulseTrain {
+                width: 0.1,
+                count: 2
+            }
+        );
+        assert!(!shape.set_parameter(PumpParameter::SinePeriod, 1.).unwrap());
+        let mut ramp = PumpEnvelope::Ramp { duration: 1. };
+        assert!(ramp
+            .set_parameter(PumpParameter::RampDuration, -1.)
+            .is_err());
+        assert!(ramp.set_parameter(PumpParameter::RampDuration, 2.).unwrap());
+        assert_eq!(ramp, PumpEnvelope::Ramp { duration: 2. });
+        let mut sine = PumpEnvelope::Sine {
+            depth: 0.1,
+            period: 1.,
+        };
+        assert!(sine.set_parameter(PumpParameter::SinePeriod, 0.).is_err());
+        assert_eq!(sine.value(0.25), 1.1);
+    }
+}
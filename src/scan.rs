@@ -0,0 +1,225 @@
+use std::{fmt::Write as _, path::Path};
+
+use anyhow::Context;
+
+use crate::{anyhow, Result};
+
+///a ramp of the detuning toward `to` at `rate` per unit simulation time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScanSegment {
+    pub to: f64,
+    pub rate: f64,
+}
+
+///detuning as a function of the simulation time, starting from `start` and
+///following each segment in turn
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanProtocol {
+    pub start: f64,
+    pub segments: Vec<ScanSegment>,
+}
+
+impl ScanProtocol {
+    pub fn linear(start: f64, stop: f64, rate: f64) -> Self {
+        Self::piecewise(start, vec![ScanSegment { to: stop, rate }])
+    }
+    ///scan to `stop` then tune back to `back`, the usual way to reach the single soliton state
+    pub fn backward(start: f64, stop: f64, rate: f64, back: f64, back_rate: f64) -> Self {
+        Self::piecewise(
+            start,
+            vec![
+                ScanSegment { to: stop, rate },
+                ScanSegment {
+                    to: back,
+                    rate: back_rate,
+                },
+            ],
+        )
+    }
+    pub fn piecewise(start: f64, segments: Vec<ScanSegment>) -> Self {
+        Self { start, segments }
+    }
+    pub fn check(&self) -> Result<()> {
+        match self
+            .segments
+            .iter()
+            .position(|s| !(s.rate > 0. && s.rate.is_finite()))
+        {
+            Some(i) => Err(anyhow!(
+                "scan rate of segment {} should be positive, got {}",
+                i + 1,
+                self.segments[i].rate
+            )),
+            None => Ok(()),
+        }
+    }
+    pub fn duration(&self) -> f64 {
+        self.segments
+            .iter()
+            .scan(self.start, |from, s| {
+                let d = (s.to - *from).abs() / s.rate;
+                *from = s.to;
+                Some(d)
+            })
+            .sum()
+    }
+    ///detuning at the end of the scan
+    pub fn end(&self) -> f64 {
+        self.segments.last().map_or(self.start, |s| s.to)
+    }
+    ///detuning at `time` after the scan started, `None` once the scan finished
+    pub fn detuning(&self, time: f64) -> Option<f64> {
+        let mut from = self.start;
+        let mut elapsed = time;
+        for s in self.segments.iter() {
+            let d = (s.to - from).abs() / s.rate;
+            if elapsed < d {
+                return Some(from + (s.to - from).signum() * s.rate * elapsed);
+            }
+            elapsed -= d;
+            from = s.to;
+        }
+        None
+    }
+}
+
+///intracavity power of each component sampled during a scan
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanPoint {
+    pub time: f64,
+    pub detuning: f64,
+    ///average intracavity power of each component
+    pub power: Vec<f64>,
+}
+
+///a detuning scan in progress or finished, with the recorded trace
+#[derive(Debug, Clone)]
+pub struct Scan {
+    protocol: ScanProtocol,
+    ///simulation time elapsed since the scan started
+    time: f64,
+    running: bool,
+    record: Vec<ScanPoint>,
+    ///seed of the worker when the scan started
+    seed: u64,
+}
+
+impl Scan {
+    pub fn new(protocol: ScanProtocol, seed: u64) -> Result<Self> {
+        protocol.check()?;
+        Ok(Self {
+            protocol,
+            time: 0.,
+            running: true,
+            record: Vec::new(),
+            seed,
+        })
+    }
+    pub fn protocol(&self) -> &ScanProtocol {
+        &self.protocol
+    }
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+    pub fn stop(&mut self) {
+        self.running = false;
+    }
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    pub fn record(&self) -> &[ScanPoint] {
+        &self.record
+    }
+    ///detuning after advancing `step` in time, stopping the scan if finished,
+    ///the step finishing the scan lands on its final detuning
+    pub(crate) fn advance(&mut self, step: f64) -> Option<f64> {
+        if !self.running {
+            return None;
+        }
+        self.time += step;
+        match self.protocol.detuning(self.time) {
+            Some(d) => Some(d),
+            None => {
+                self.running = false;
+                Some(self.protocol.end())
+            }
+        }
+    }
+    pub(crate) fn push(&mut self, detuning: f64, power: Vec<f64>) {
+        self.record.push(ScanPoint {
+            time: self.time,
+            detuning,
+            power,
+        });
+    }
+    ///write the record as csv rows of `time, detuning, power of each component`,
+    ///preceded by a comment line of the seed
+    pub fn save_csv(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let components = self.record.first().map_or(0, |p| p.power.len());
+        let mut content = format!("# seed {}\ntime,detuning", self.seed);
+        (1..=components).for_each(|i| write!(content, ",power{}", i).unwrap());
+        content.push('\n');
+        for p in self.record.iter() {
+            write!(content, "{},{}", p.time, p.detuning).unwrap();
+            p.power
+                .iter()
+                .for_each(|x| write!(content, ",{}", x).unwrap());
+            content.push('\n');
+        }
+        std::fs::write(path, content)
+            .with_context(|| format!("writing scan record {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detuning_endpoints() {
+        let protocol = ScanProtocol::backward(-5., 10., 2., 4., 0.5);
+        assert_eq!(protocol.duration(), 7.5 + 12.);
+        assert_eq!(protocol.end(), 4.);
+        assert_eq!(protocol.detuning(0.), Some(-5.));
+        assert_eq!(protocol.detuning(2.5), Some(0.));
+        //the first segment ends where the second starts
+        assert_eq!(protocol.detuning(7.5), Some(10.));
+        assert_eq!(protocol.detuning(9.5), Some(9.));
+        assert!((protocol.detuning(19.5 - 1e-9).unwrap() - 4.).abs() < 1e-9);
+        assert_eq!(protocol.detuning(19.5), None);
+        assert_eq!(protocol.detuning(100.), None);
+    }
+
+    #[test]
+    fn empty_protocol() {
+        let protocol = ScanProtocol::piecewise(3., Vec::new());
+        assert_eq!(protocol.duration(), 0.);
+        assert_eq!(protocol.end(), 3.);
+        assert_eq!(protocol.detuning(0.), None);
+    }
+
+    #[test]
+    fn check_rates() {
+        assert!(ScanProtocol::linear(0., 1., 0.).check().is_err());
+        assert!(ScanProtocol::linear(0., 1., f64::NAN).check().is_err());
+        assert!(ScanProtocol::backward(0., 1., 1., 0., -1.).check().is_err());
+        assert!(ScanProtocol::linear(0., 1., 1.).check().is_ok());
+    }
+
+    #[test]
+    fn scan_lands_on_final_detuning() {
+        //the duration 1.05 isn't a multiple of the step
+        let mut scan = Scan::new(ScanProtocol::linear(0., 2.1, 2.), 0).unwrap();
+        let mut last = None;
+        while let Some(d) = scan.advance(0.1) {
+            last = Some(d);
+        }
+        assert!(!scan.is_running());
+        assert_eq!(last, Some(2.1));
+        assert_eq!(scan.advance(0.1), None);
+    }
+}
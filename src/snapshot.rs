@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use anyhow::Context;
+use lle::num_complex::Complex64;
+use serde::{Deserialize, Serialize};
+
+use crate::{Result, Topology, WorkerProperty};
+
+///complete state of a [`Worker`](crate::Worker) saved as json,
+///the random generator restarts from the recorded seed when loaded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub topology: Topology,
+    pub property: WorkerProperty,
+    pub time: f64,
+    pub temperature: [f64; 2],
+    ///field of each component as `[re, im]` pairs
+    pub states: Vec<Vec<[f64; 2]>>,
+}
+
+impl Snapshot {
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("creating snapshot {}", path.display()))?;
+        serde_json::to_writer(std::io::BufWriter::new(file), self)
+            .with_context(|| format!("writing snapshot {}", path.display()))
+    }
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("opening snapshot {}", path.display()))?;
+        serde_json::from_reader(std::io::BufReader::new(file))
+            .with_context(|| format!("parsing snapshot {}", path.display()))
+    }
+}
+
+pub(crate) fn encode_state(state: &[Complex64]) -> Vec<[f64; 2]> {
+    state.iter().map(|x| [x.re, x.im]).collect()
+}
+
+pub(crate) fn decode_state(state: &[[f64; 2]]) -> Vec<Complex64> {
+    state
+        .iter()
+        .map(|&[re, im]| Complex64::new(re, im))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Component, LossProfile, ModeTable, Raman, Thermal, Worker, WorkerUpdate};
+
+    fn table(modes: usize, value: impl Fn(f64) -> f64) -> ModeTable {
+        let (lower, upper) = crate::mode_range(modes);
+        let content: String = (lower..=upper)
+            .map(|m| format!("{},{}\n", m, value(m as f64)))
+            .collect();
+        ModeTable::parse_csv(&content).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let modes = 16;
+        let mut worker = Worker::with_seed(Topology::Coupled, modes, 3);
+        worker
+            .set_loss_profile(
+                Component::First,
+                LossProfile::Table(table(modes, |m| 1e-3 * m * m)),
+            )
+            .unwrap();
+        worker
+            .set_dint(Component::Second, Some(table(modes, |m| 0.02 * m * m)))
+            .unwrap();
+        worker.set_property(WorkerUpdate::DriveAmplitude(Component::First, -3, 0.5));
+        worker.set_property(WorkerUpdate::DriveOffset(Component::First, -3, 0.1));
+        worker.set_property(WorkerUpdate::DriveAmplitude(Component::Second, 2, 0.2));
+        worker.set_raman(Component::First, Some(Raman::default()));
+        worker.set_thermal(Component::Second, Some(Thermal::default()));
+        for _ in 0..3 {
+            worker.tick();
+        }
+        let path = std::env::temp_dir().join(format!("lle_snapshot_{}.json", std::process::id()));
+        worker.save(&path).unwrap();
+        let loaded = Worker::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.topology(), worker.topology());
+        assert_eq!(loaded.get_property(), worker.get_property());
+        assert_eq!(loaded.get_state(), worker.get_state());
+        assert_eq!(loaded.time(), worker.time());
+        for c in Component::ALL {
+            assert_eq!(loaded.temperature(c), worker.temperature(c));
+        }
+        assert!(worker.temperature(Component::Second) != 0.);
+    }
+
+    #[test]
+    fn mismatched_snapshot() {
+        let snapshot = Worker::with_seed(Topology::Coupled, 16, 0).snapshot();
+        let mut fewer = snapshot.clone();
+        fewer.states.pop();
+        let error = Worker::from_snapshot(fewer).err().unwrap().to_string();
+        assert!(error.contains("expects 2 fields, got 1"), "{}", error);
+        let mut shorter = snapshot.clone();
+        shorter.states[1].pop();
+        let error = Worker::from_snapshot(shorter).err().unwrap().to_string();
+        assert!(
+            error.contains("field of 15 points while simulating 16 modes"),
+            "{}",
+            error
+        );
+        let mut single = snapshot;
+        single.topology = Topology::Single;
+        assert!(Worker::from_snapshot(single).is_err());
+    }
+}
@@ -0,0 +1,578 @@
+use std::f64::consts::PI;
+
+use lle::num_complex::Complex64;
+
+use crate::{Component, ComponentProperty, Coupling, Topology, WorkerProperty};
+
+///intracavity powers `ρ` of the homogeneous steady states, the real roots of
+///`ρ * (loss² + (alpha - ρ)²) = |pump|²` in ascending order
+pub fn cw_intensities(alpha: f64, loss: f64, pump: f64) -> Vec<f64> {
+    //ρ³ + b * ρ² + c * ρ + d = 0
+    let (b, c, d) = (-2. * alpha, alpha * alpha + loss * loss, -pump * pump);
+    //depressed as t³ + p * t + q = 0 with ρ = t - b / 3
+    let p = c - b * b / 3.;
+    let q = 2. * b * b * b / 27. - b * c / 3. + d;
+    let shift = -b / 3.;
+    let discriminant = 4. * p * p * p + 27. * q * q;
+    let mut roots = if discriminant < 0. {
+        let r = 2. * (-p / 3.).sqrt();
+        let phi = (3. * q / (2. * p) * (-3. / p).sqrt()).clamp(-1., 1.).acos() / 3.;
+        (0..3)
+            .map(|k| r * (phi - 2. * PI * k as f64 / 3.).cos() + shift)
+            .collect::<Vec<_>>()
+    } else {
+        let s = (q * q / 4. + p * p * p / 27.).sqrt();
+        vec![(-q / 2. + s).cbrt() + (-q / 2. - s).cbrt() + shift]
+    };
+    roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    //the power is never negative, rounding errors aside
+    roots.into_iter().map(|x| x.max(0.)).collect()
+}
+
+///the homogeneous steady state field with power `intensity`,
+///`pump / (loss + i * (alpha - intensity))`
+pub fn cw_field(alpha: f64, loss: f64, pump: Complex64, intensity: f64) -> Complex64 {
+    pump / Complex64::new(loss, alpha - intensity)
+}
+
+///homogeneous steady state of every simulated component
+#[derive(Debug, Clone, PartialEq)]
+pub struct CwState {
+    pub fields: Vec<Complex64>,
+    ///largest growth rate of homogeneous perturbations, stable if negative
+    pub growth: f64,
+}
+
+impl CwState {
+    pub fn powers(&self) -> Vec<f64> {
+        self.fields.iter().map(|x| x.norm_sqr()).collect()
+    }
+    pub fn is_stable(&self) -> bool {
+        self.growth < 0.
+    }
+}
+
+///parameters of a component seen by its pumped mode, a cw pump is assumed
+#[derive(Debug, Clone, Copy)]
+struct Homogeneous {
+    loss: f64,
+    ///detuning including the integrated dispersion at the pumped mode
+    alpha: f64,
+    pump: Complex64,
+}
+
+impl Homogeneous {
+    fn new(p: &ComponentProperty) -> Self {
+        Self {
+            loss: p.loss + p.loss_profile.value(0),
+            alpha: p.alpha + p.dint.as_ref().and_then(|t| t.get(0)).unwrap_or_default(),
+            pump: Complex64::from_polar(p.pump, p.pump_phase),
+        }
+    }
+}
+
+///all homogeneous steady states, sorted by the power of the first component,
+///the two components of [`Topology::Coupled`] mix by the coupling at the pumped mode and xpm
+pub fn cw_states(topology: Topology, property: &WorkerProperty) -> Vec<CwState> {
+    let first = Homogeneous::new(&property.components[Component::First.index()]);
+    let (params, fields) = match topology {
+        Topology::Single => {
+            let fields = cw_intensities(first.alpha, first.loss, first.pump.norm())
+                .into_iter()
+                .map(|p| vec![cw_field(first.alpha, first.loss, first.pump, p)])
+                .collect();
+            (vec![first], fields)
+        }
+        Topology::Coupled => {
+            let second = Homogeneous::new(&property.components[Component::Second.index()]);
+            let coupling = property.couplings.get(&0).copied();
+            let fields = coupled_fields([first, second], coupling, property.xpm);
+            (vec![first, second], fields)
+        }
+    };
+    let coupling = property.couplings.get(&0).copied();
+    let linear: Vec<(Complex64, Complex64)> = params
+        .iter()
+        .map(|p| {
+            let l = -Complex64::new(p.loss, p.alpha);
+            (l, l)
+        })
+        .collect();
+    let mut states: Vec<CwState> = fields
+        .into_iter()
+        .map(|f: Vec<Complex64>| CwState {
+            growth: eigenvalues(&jacobian(&linear, [coupling; 2], property.xpm, &f))
+                .into_iter()
+                .map(|x| x.re)
+                .fold(f64::NEG_INFINITY, f64::max),
+            fields: f,
+        })
+        .collect();
+    states.sort_by(|a, b| a.fields[0].norm_sqr().total_cmp(&b.fields[0].norm_sqr()));
+    states
+}
+
+///the bistability curve, steady states at each detuning of the first component,
+///the other component keeps its detuning offset to the first
+pub fn s_curve(
+    topology: Topology,
+    property: &WorkerProperty,
+    alphas: impl IntoIterator<Item = f64>,
+) -> Vec<(f64, CwState)> {
+    let mut property = property.clone();
+    let mut curve = Vec::new();
+    for alpha in alphas {
+        let shift = alpha - property.components[Component::First.index()].alpha;
+        property
+            .components
+            .iter_mut()
+            .for_each(|c| c.alpha += shift);
+        curve.extend(
+            cw_states(topology, &property)
+                .into_iter()
+                .map(|s| (alpha, s)),
+        );
+    }
+    curve
+}
+
+///steady fields of two coupled components, empty if a loss isn't positive
+///
+///the fields are linear in the pump for given powers `P1`, `P2`,
+///so the states are the roots of `|A(P1, P2)|² = P` found by newton's method
+///seeded on a grid bounded by the power balance `Σ loss * P = Σ Re(F* * A)`
+fn coupled_fields(
+    params: [Homogeneous; 2],
+    coupling: Option<Coupling>,
+    xpm: f64,
+) -> Vec<Vec<Complex64>> {
+    const GRID: usize = 64;
+    const MAX_ITERATION: usize = 50;
+    let min_loss = params[0].loss.min(params[1].loss);
+    if min_loss <= 0. {
+        return Vec::new();
+    }
+    let bound = ((params[0].pump.norm() + params[1].pump.norm()) / min_loss).powi(2);
+    if bound == 0. {
+        return vec![vec![Complex64::default(); 2]];
+    }
+    let (c12, c21) = coupling.map_or((Complex64::default(), Complex64::default()), |c| {
+        (
+            Complex64::from_polar(c.strength, c.phase),
+            Complex64::from_polar(c.strength, -c.phase),
+        )
+    });
+    //`(loss + i * (alpha - P - xpm * P')) * A - i * c * A' = F` of both components
+    let fields = |p: [f64; 2]| -> Option<[Complex64; 2]> {
+        let m11 = Complex64::new(params[0].loss, params[0].alpha - p[0] - xpm * p[1]);
+        let m22 = Complex64::new(params[1].loss, params[1].alpha - p[1] - xpm * p[0]);
+        let (m12, m21) = (-Complex64::i() * c12, -Complex64::i() * c21);
+        let det = m11 * m22 - m12 * m21;
+        if det.norm() < f64::EPSILON {
+            return None;
+        }
+        let (f1, f2) = (params[0].pump, params[1].pump);
+        Some([(f1 * m22 - m12 * f2) / det, (m11 * f2 - m21 * f1) / det])
+    };
+    let residual = |p: [f64; 2]| -> Option<[f64; 2]> {
+        fields(p).map(|a| [a[0].norm_sqr() - p[0], a[1].norm_sqr() - p[1]])
+    };
+    //denser sampling at low power, where the roots lie close to each other
+    let grid: Vec<f64> = (0..=GRID)
+        .map(|i| bound * (i as f64 / GRID as f64).powi(2))
+        .collect();
+    let values: Vec<Vec<Option<[f64; 2]>>> = grid
+        .iter()
+        .map(|&p1| grid.iter().map(|&p2| residual([p1, p2])).collect())
+        .collect();
+    let tolerance = 1e-10 * (1. + bound);
+    let mut roots: Vec<[f64; 2]> = Vec::new();
+    for i in 0..GRID {
+        for j in 0..GRID {
+            let corners = [
+                values[i][j],
+                values[i + 1][j],
+                values[i][j + 1],
+                values[i + 1][j + 1],
+            ];
+            let changes = |k: usize| {
+                let v: Vec<f64> = corners.iter().flatten().map(|r| r[k]).collect();
+                v.iter().any(|&x| x <= 0.) && v.iter().any(|&x| x >= 0.)
+            };
+            if !(changes(0) && changes(1)) {
+                continue;
+            }
+            let mut p = [(grid[i] + grid[i + 1]) / 2., (grid[j] + grid[j + 1]) / 2.];
+            for _ in 0..MAX_ITERATION {
+                let Some(r) = residual(p) else {
+                    break;
+                };
+                if r[0].abs().max(r[1].abs()) < tolerance {
+                    if roots
+                        .iter()
+                        .all(|q| (q[0] - p[0]).abs().max((q[1] - p[1]).abs()) > 1e-6 * (1. + bound))
+                    {
+                        roots.push(p);
+                    }
+                    break;
+                }
+                //finite difference jacobian
+                let h = 1e-7 * (1. + p[0].max(p[1]));
+                let (Some(r1), Some(r2)) = (residual([p[0] + h, p[1]]), residual([p[0], p[1] + h]))
+                else {
+                    break;
+                };
+                let j = [
+                    [(r1[0] - r[0]) / h, (r2[0] - r[0]) / h],
+                    [(r1[1] - r[1]) / h, (r2[1] - r[1]) / h],
+                ];
+                let det = j[0][0] * j[1][1] - j[0][1] * j[1][0];
+                if det == 0. {
+                    break;
+                }
+                p = [
+                    (p[0] - (r[0] * j[1][1] - r[1] * j[0][1]) / det).max(0.),
+                    (p[1] - (j[0][0] * r[1] - j[1][0] * r[0]) / det).max(0.),
+                ];
+            }
+        }
+    }
+    roots
+        .into_iter()
+        .filter_map(|p| fields(p).map(|a| a.to_vec()))
+        .collect()
+}
+
+///linearization of the homogeneous state `fields` against perturbations at the modes `±μ`,
+///acting on `(δA1(μ), δA1*(-μ), δA2(μ), δA2*(-μ))` of the simulated components,
+///`linear` is the linear operator of each component at `μ` and `-μ`,
+///`coupling` the couplings at `μ` and `-μ`
+pub(crate) fn jacobian(
+    linear: &[(Complex64, Complex64)],
+    coupling: [Option<Coupling>; 2],
+    xpm: f64,
+    fields: &[Complex64],
+) -> Vec<Vec<Complex64>> {
+    let i = Complex64::i();
+    let n = fields.len();
+    let mut m = vec![vec![Complex64::default(); 2 * n]; 2 * n];
+    for j in 0..n {
+        let a = fields[j];
+        let (u, v) = (2 * j, 2 * j + 1);
+        //power of the other component modulating the phase by xpm
+        let cross = (0..n)
+            .filter(|&k| k != j)
+            .map(|k| fields[k].norm_sqr())
+            .sum::<f64>()
+            * xpm;
+        let phase = i * (2. * a.norm_sqr() + cross);
+        m[u][u] = linear[j].0 + phase;
+        m[u][v] = i * a * a;
+        m[v][v] = (linear[j].1 + phase).conj();
+        m[v][u] = (i * a * a).conj();
+        for k in (0..n).filter(|&k| k != j) {
+            let b = fields[k];
+            let (uk, vk) = (2 * k, 2 * k + 1);
+            //the first component receives `g * exp(i * φ)`, the second `g * exp(-i * φ)`
+            let [plus, minus] = coupling.map(|c| {
+                c.map_or(Complex64::default(), |c| {
+                    let phase = if j == 0 { c.phase } else { -c.phase };
+                    Complex64::from_polar(c.strength, phase)
+                })
+            });
+            m[u][uk] = i * xpm * a * b.conj() + i * plus;
+            m[u][vk] = i * xpm * a * b;
+            m[v][vk] = (i * xpm * a * b.conj() + i * minus).conj();
+            m[v][uk] = m[u][vk].conj();
+        }
+    }
+    m
+}
+
+///eigenvalues of a small complex matrix, the roots of its characteristic polynomial
+///from the Faddeev-LeVerrier algorithm found by the Durand-Kerner method
+pub(crate) fn eigenvalues(m: &[Vec<Complex64>]) -> Vec<Complex64> {
+    let n = m.len();
+    //coefficients of the monic characteristic polynomial, lowest order first
+    let mut coefficients = vec![Complex64::default(); n + 1];
+    coefficients[n] = Complex64::new(1., 0.);
+    let mut power = vec![vec![Complex64::default(); n]; n];
+    for k in 1..=n {
+        //M_k = A * M_{k-1} + c_{n-k+1} * I
+        let mut next = vec![vec![Complex64::default(); n]; n];
+        for r in 0..n {
+            for c in 0..n {
+                next[r][c] = (0..n).map(|l| m[r][l] * power[l][c]).sum();
+            }
+            next[r][r] += coefficients[n - k + 1];
+        }
+        power = next;
+        let trace: Complex64 = (0..n)
+            .map(|r| (0..n).map(|l| m[r][l] * power[l][r]).sum::<Complex64>())
+            .sum();
+        coefficients[n - k] = -trace / k as f64;
+    }
+    let evaluate = |z: Complex64| {
+        coefficients
+            .iter()
+            .rev()
+            .fold(Complex64::default(), |s, &c| s * z + c)
+    };
+    let radius = 1.
+        + coefficients[..n]
+            .iter()
+            .map(|c| c.norm())
+            .fold(0., f64::max);
+    //distinct starting points on a circle enclosing all the roots
+    let mut roots: Vec<Complex64> = (0..n)
+        .map(|k| Complex64::from_polar(radius, 0.4 + 2. * PI * k as f64 / n as f64))
+        .collect();
+    for _ in 0..1000 {
+        let mut change = 0f64;
+        for k in 0..n {
+            let denominator: Complex64 = (0..n)
+                .filter(|&l| l != k)
+                .map(|l| roots[k] - roots[l])
+                .product();
+            let delta = evaluate(roots[k]) / denominator;
+            if delta.is_finite() {
+                roots[k] -= delta;
+                change = change.max(delta.norm());
+            }
+        }
+        if change < 1e-14 * radius {
+            break;
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Complex64, b: Complex64, tolerance: f64) -> bool {
+        (a - b).norm() < tolerance * (1. + b.norm())
+    }
+
+    ///each of `expected` matches a distinct eigenvalue in `lambda`
+    fn assert_spectrum(mut lambda: Vec<Complex64>, expected: &[Complex64]) {
+        assert_eq!(lambda.len(), expected.len());
+        for &e in expected {
+            let nearest = (0..lambda.len())
+                .min_by(|&x, &y| (lambda[x] - e).norm().total_cmp(&(lambda[y] - e).norm()))
+                .unwrap();
+            assert!(
+                close(lambda[nearest], e, 1e-8),
+                "{} vs {}",
+                lambda[nearest],
+                e
+            );
+            lambda.remove(nearest);
+        }
+    }
+
+    ///`-(loss + i * alpha) * A + i * (|A|² + xpm * |A'|²) * A + i * c * A' + F` of both components
+    fn coupled_rhs(
+        params: &[Homogeneous; 2],
+        coupling: Option<Coupling>,
+        xpm: f64,
+        a: [Complex64; 2],
+    ) -> [Complex64; 2] {
+        let i = Complex64::i();
+        let c = coupling.map_or([Complex64::default(); 2], |c| {
+            [
+                Complex64::from_polar(c.strength, c.phase),
+                Complex64::from_polar(c.strength, -c.phase),
+            ]
+        });
+        [0, 1].map(|j| {
+            let (p, k) = (params[j], 1 - j);
+            -Complex64::new(p.loss, p.alpha) * a[j]
+                + i * (a[j].norm_sqr() + xpm * a[k].norm_sqr()) * a[j]
+                + i * c[j] * a[k]
+                + p.pump
+        })
+    }
+
+    #[test]
+    fn cw_intensities_residual() {
+        for (alpha, loss, pump, count) in [
+            (-5., 1., 3.94, 1),
+            (0., 1., 1., 1),
+            (8., 1., 3.94, 3),
+            (3., 0.5, 1.2, 3),
+            (1., 1., 0., 1),
+        ] {
+            let roots = cw_intensities(alpha, loss, pump);
+            assert_eq!(roots.len(), count, "alpha {}, pump {}", alpha, pump);
+            assert!(roots.windows(2).all(|w| w[0] <= w[1]));
+            for rho in roots {
+                let residual = rho * (loss * loss + (alpha - rho).powi(2)) - pump * pump;
+                assert!(residual.abs() < 1e-9 * (1. + pump * pump), "root {}", rho);
+                let field = cw_field(alpha, loss, Complex64::new(pump, 0.), rho);
+                assert!((field.norm_sqr() - rho).abs() < 1e-9 * (1. + rho));
+            }
+        }
+    }
+
+    #[test]
+    fn coupled_fields_residual() {
+        let component = |alpha: f64, pump: f64| Homogeneous {
+            loss: 1.,
+            alpha,
+            pump: Complex64::new(pump, 0.),
+        };
+        for (params, strength, xpm) in [
+            ([component(8., 3.94), component(8., 0.)], 1., 2.),
+            ([component(-5., 3.94), component(-3., 0.)], 1., 2.),
+            ([component(4., 2.), component(6., 1.5)], 0.5, 0.),
+            ([component(2., 3.), component(2., 3.)], 2., 1.),
+        ] {
+            let coupling = Some(Coupling {
+                strength,
+                phase: 0.3,
+            });
+            let states = coupled_fields(params, coupling, xpm);
+            assert!(!states.is_empty());
+            for a in states {
+                let r = coupled_rhs(&params, coupling, xpm, [a[0], a[1]]);
+                assert!(r[0].norm().max(r[1].norm()) < 1e-7, "{:?}: {:?}", a, r);
+            }
+        }
+    }
+
+    #[test]
+    fn uncoupled_fields_match_single() {
+        let params = [8., 3.].map(|alpha| Homogeneous {
+            loss: 1.,
+            alpha,
+            pump: Complex64::new(3.94, 0.),
+        });
+        let states = coupled_fields(params, None, 0.);
+        //every combination of the states of each component alone
+        let single: Vec<Vec<f64>> = params
+            .iter()
+            .map(|p| cw_intensities(p.alpha, p.loss, p.pump.norm()))
+            .collect();
+        assert_eq!(states.len(), single[0].len() * single[1].len());
+        for a in states {
+            for (x, s) in a.iter().zip(single.iter()) {
+                assert!(s.iter().any(|p| (x.norm_sqr() - p).abs() < 1e-7 * (1. + p)));
+            }
+        }
+    }
+
+    #[test]
+    fn jacobian_of_single_resonator() {
+        let (alpha, loss) = (8., 1.);
+        for rho in cw_intensities(alpha, loss, 3.94) {
+            let a = cw_field(alpha, loss, Complex64::new(3.94, 0.), rho);
+            let l = -Complex64::new(loss, alpha);
+            let lambda = eigenvalues(&jacobian(&[(l, l)], [None; 2], 0., &[a]));
+            //`-loss ± sqrt(ρ² - (alpha - 2ρ)²)`
+            let root = Complex64::new(rho * rho - (alpha - 2. * rho).powi(2), 0.).sqrt();
+            assert_spectrum(lambda, &[-loss - root, -loss + root]);
+        }
+    }
+
+    #[test]
+    fn jacobian_matches_finite_difference() {
+        let params = [
+            Homogeneous {
+                loss: 1.,
+                alpha: 3.,
+                pump: Complex64::new(2., 0.5),
+            },
+            Homogeneous {
+                loss: 0.8,
+                alpha: -1.,
+                pump: Complex64::new(0.5, 0.),
+            },
+        ];
+        let coupling = Some(Coupling {
+            strength: 0.7,
+            phase: 0.4,
+        });
+        let xpm = 1.5;
+        let a = [Complex64::new(0.6, -0.3), Complex64::new(-0.2, 0.9)];
+        let linear: Vec<_> = params
+            .iter()
+            .map(|p| {
+                let l = -Complex64::new(p.loss, p.alpha);
+                (l, l)
+            })
+            .collect();
+        let m = jacobian(&linear, [coupling; 2], xpm, &a);
+        let h = 1e-6;
+        for k in 0..2 {
+            let shifted = |d: Complex64| {
+                let mut b = a;
+                b[k] += d;
+                coupled_rhs(&params, coupling, xpm, b)
+            };
+            let (x, y) = (
+                shifted(Complex64::new(h, 0.)),
+                shifted(Complex64::new(0., h)),
+            );
+            let (x0, y0) = (
+                shifted(Complex64::new(-h, 0.)),
+                shifted(Complex64::new(0., -h)),
+            );
+            for j in 0..2 {
+                let dx = (x[j] - x0[j]) / (2. * h);
+                let dy = (y[j] - y0[j]) / (2. * h);
+                //wirtinger derivatives by `A` and `A*`
+                let (da, dc) = (
+                    (dx - Complex64::i() * dy) / 2.,
+                    (dx + Complex64::i() * dy) / 2.,
+                );
+                assert!(close(m[2 * j][2 * k], da, 1e-7));
+                assert!(close(m[2 * j][2 * k + 1], dc, 1e-7));
+                assert!(close(m[2 * j + 1][2 * k], dc.conj(), 1e-7));
+                assert!(close(m[2 * j + 1][2 * k + 1], da.conj(), 1e-7));
+            }
+        }
+    }
+
+    #[test]
+    fn eigenvalues_of_known_spectra() {
+        let c = |re: f64, im: f64| Complex64::new(re, im);
+        let spectra = |m: Vec<Vec<Complex64>>, expected: Vec<Complex64>| {
+            assert_spectrum(eigenvalues(&m), &expected)
+        };
+        //diagonal
+        spectra(
+            vec![
+                vec![c(1., 0.), c(0., 0.), c(0., 0.)],
+                vec![c(0., 0.), c(-2., 1.), c(0., 0.)],
+                vec![c(0., 0.), c(0., 0.), c(0., 3.)],
+            ],
+            vec![c(1., 0.), c(-2., 1.), c(0., 3.)],
+        );
+        //rotation generator
+        spectra(
+            vec![vec![c(0., 0.), c(-1., 0.)], vec![c(1., 0.), c(0., 0.)]],
+            vec![c(0., 1.), c(0., -1.)],
+        );
+        //upper triangular with a repeated eigenvalue
+        spectra(
+            vec![
+                vec![c(2., 0.), c(1., 1.), c(3., 0.), c(0., 1.)],
+                vec![c(0., 0.), c(2., 0.), c(-1., 0.), c(5., 0.)],
+                vec![c(0., 0.), c(0., 0.), c(-1., 2.), c(1., 0.)],
+                vec![c(0., 0.), c(0., 0.), c(0., 0.), c(0.5, -0.5)],
+            ],
+            vec![c(2., 0.), c(2., 0.), c(-1., 2.), c(0.5, -0.5)],
+        );
+        //similar to diag(1, 2i) by P = [[1, 1], [1, 2]]
+        let (d1, d2) = (c(1., 0.), c(0., 2.));
+        spectra(
+            vec![
+                vec![2. * d1 - d2, d2 - d1],
+                vec![2. * d1 - 2. * d2, 2. * d2 - d1],
+            ],
+            vec![d1, d2],
+        );
+    }
+}
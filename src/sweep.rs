@@ -0,0 +1,514 @@
+use std::{
+    fmt::Write,
+    path::Path,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+};
+
+use anyhow::Context;
+use lle::num_complex::Complex64;
+use plotters::prelude::*;
+use rustfft::FftPlanner;
+
+use crate::{
+    anyhow, average_power, Component, Config, InitialCondition, Result, Topology, Worker,
+    WorkerProperty, WorkerUpdate,
+};
+
+///relative standard deviation of the power below which the state is stationary
+const STATIONARY: f64 = 1e-3;
+///contrast of the intensity profile below which the state is homogeneous
+const HOMOGENEOUS: f64 = 1e-2;
+///fraction of the round trip above the half maximum separating rolls from solitons
+const ROLL_DUTY: f64 = 0.25;
+///fraction of the power oscillation in its dominant frequency for a breather
+const PERIODIC: f64 = 0.5;
+
+///regime reached by the first component at the end of a sweep run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Regime {
+    ///stationary homogeneous state
+    Cw,
+    ///stationary pattern filling the round trip, with the number of rolls
+    Rolls(usize),
+    ///stationary localized pulses, with their number
+    Solitons(usize),
+    ///periodically oscillating power
+    Breather,
+    ///irregularly oscillating power
+    Chaos,
+    ///the initial condition couldn't be generated at this point
+    Failed,
+}
+
+impl Regime {
+    pub const NAMES: [&'static str; 6] = ["cw", "rolls", "solitons", "breather", "chaos", "failed"];
+    pub fn name(&self) -> &'static str {
+        Self::NAMES[self.kind()]
+    }
+    ///number of rolls or solitons, 0 for the other regimes
+    pub fn count(&self) -> usize {
+        match *self {
+            Regime::Rolls(n) | Regime::Solitons(n) => n,
+            _ => 0,
+        }
+    }
+    fn kind(&self) -> usize {
+        match self {
+            Regime::Cw => 0,
+            Regime::Rolls(_) => 1,
+            Regime::Solitons(_) => 2,
+            Regime::Breather => 3,
+            Regime::Chaos => 4,
+            Regime::Failed => 5,
+        }
+    }
+    fn color(kind: usize) -> RGBColor {
+        [
+            RGBColor(190, 190, 190),
+            RGBColor(60, 110, 200),
+            RGBColor(40, 160, 70),
+            RGBColor(240, 150, 30),
+            RGBColor(200, 40, 40),
+            RGBColor(40, 40, 40),
+        ][kind]
+    }
+}
+
+///classified final state at one grid point, the observables are NaN for [`Regime::Failed`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepPoint {
+    pub alpha: f64,
+    pub pump: f64,
+    pub regime: Regime,
+    ///average power over the observation window
+    pub power: f64,
+    ///relative standard deviation of the power over the observation window
+    pub variation: f64,
+    ///peak to peak intensity of the final field relative to its average
+    pub contrast: f64,
+}
+
+///evenly spaced values from `start` to `end` inclusive
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepAxis {
+    pub start: f64,
+    pub end: f64,
+    pub steps: usize,
+}
+
+impl SweepAxis {
+    pub fn values(&self) -> Vec<f64> {
+        if self.steps == 1 {
+            return vec![self.start];
+        }
+        (0..self.steps)
+            .map(|i| self.start + (self.end - self.start) * i as f64 / (self.steps - 1) as f64)
+            .collect()
+    }
+    ///spacing of the values, 1 for a single value
+    fn spacing(&self) -> f64 {
+        if self.steps > 1 && self.end != self.start {
+            ((self.end - self.start) / (self.steps - 1) as f64).abs()
+        } else {
+            1.
+        }
+    }
+    fn range(&self) -> std::ops::Range<f64> {
+        let half = self.spacing() / 2.;
+        self.start.min(self.end) - half..self.start.max(self.end) + half
+    }
+}
+
+impl FromStr for SweepAxis {
+    type Err = anyhow::Error;
+    ///parse `start:end:steps`
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.split(':').collect();
+        if parts.len() != 3 {
+            return Err(anyhow!("expect start:end:steps, got '{}'", s));
+        }
+        let axis = SweepAxis {
+            start: parts[0].trim().parse()?,
+            end: parts[1].trim().parse()?,
+            steps: parts[2].trim().parse()?,
+        };
+        if axis.steps == 0 || !axis.start.is_finite() || !axis.end.is_finite() {
+            return Err(anyhow!("illegal axis '{}'", s));
+        }
+        Ok(axis)
+    }
+}
+
+///existence map of the regimes over `alpha` × `pump` of the first component,
+///the other component keeps its detuning offset to the first
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sweep {
+    pub alpha: SweepAxis,
+    pub pump: SweepAxis,
+    ///ticks evolved at each point, including the observation window
+    pub ticks: u64,
+    ///last ticks whose power is observed to classify the regime
+    pub window: u64,
+    pub threads: usize,
+}
+
+///result of a [`Sweep`], points ordered by pump then alpha
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepMap {
+    pub alpha: SweepAxis,
+    pub pump: SweepAxis,
+    ///seed shared by all the points
+    pub seed: u64,
+    pub points: Vec<SweepPoint>,
+}
+
+impl Sweep {
+    pub fn new(alpha: SweepAxis, pump: SweepAxis) -> Self {
+        Self {
+            alpha,
+            pump,
+            ticks: 2000,
+            window: 200,
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+    ///run every grid point from `config` in parallel, all points share the seed of `config`
+    pub fn run(&self, config: &Config) -> Result<SweepMap> {
+        if self.window < 2 || self.window > self.ticks {
+            return Err(anyhow!(
+                "observation window should be within 2 and {} ticks, got {}",
+                self.ticks,
+                self.window
+            ));
+        }
+        if self.alpha.steps == 0 || self.pump.steps == 0 {
+            return Err(anyhow!("sweep axes should have at least one step"));
+        }
+        let property = config.property()?;
+        let grid: Vec<(f64, f64)> = self
+            .pump
+            .values()
+            .into_iter()
+            .flat_map(|p| self.alpha.values().into_iter().map(move |a| (a, p)))
+            .collect();
+        let next = &AtomicUsize::new(0);
+        let mut points = vec![None; grid.len()];
+        log::info!(
+            "sweeping {} points on {} threads with seed {}",
+            grid.len(),
+            self.threads,
+            property.seed
+        );
+        std::thread::scope(|s| -> Result<()> {
+            let (tx, rx) = mpsc::channel();
+            for _ in 0..self.threads.clamp(1, grid.len()) {
+                let tx = tx.clone();
+                let (grid, property) = (&grid, &property);
+                s.spawn(move || loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(&(alpha, pump)) = grid.get(i) else {
+                        break;
+                    };
+                    let point =
+                        self.run_point(config.topology, property, &config.initial, alpha, pump);
+                    if tx.send((i, point)).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(tx);
+            for (done, (i, point)) in rx.into_iter().enumerate() {
+                let (alpha, pump) = grid[i];
+                match point {
+                    Ok(p) => points[i] = Some(p),
+                    Err(e) => {
+                        //stop the other threads from taking new points
+                        next.store(grid.len(), Ordering::Relaxed);
+                        return Err(e.context(format!("at alpha {}, pump {}", alpha, pump)));
+                    }
+                }
+                if (done + 1) * 10 / grid.len() != done * 10 / grid.len() {
+                    log::info!("{}% finished", (done + 1) * 100 / grid.len());
+                }
+            }
+            Ok(())
+        })?;
+        Ok(SweepMap {
+            alpha: self.alpha,
+            pump: self.pump,
+            seed: property.seed,
+            points: points.into_iter().map(Option::unwrap).collect(),
+        })
+    }
+    fn run_point(
+        &self,
+        topology: Topology,
+        property: &WorkerProperty,
+        initial: &InitialCondition,
+        alpha: f64,
+        pump: f64,
+    ) -> Result<SweepPoint> {
+        let mut worker = Worker::from_property(topology, property.clone())?;
+        let first = property.components[Component::First.index()].alpha;
+        for &c in worker.components() {
+            let offset = property.components[c.index()].alpha - first;
+            worker.set_property(WorkerUpdate::Alpha(c, alpha + offset));
+        }
+        worker.set_property(WorkerUpdate::Pump(Component::First, pump));
+        //the initial condition may exist only in a part of the map, like bright solitons
+        if let Err(e) = worker.reset(initial) {
+            log::warn!("at alpha {}, pump {}: {:#}", alpha, pump, e);
+            return Ok(SweepPoint {
+                alpha,
+                pump,
+                regime: Regime::Failed,
+                power: f64::NAN,
+                variation: f64::NAN,
+                contrast: f64::NAN,
+            });
+        }
+        for _ in self.window..self.ticks {
+            worker.tick();
+        }
+        let powers: Vec<f64> = (0..self.window)
+            .map(|_| {
+                worker.tick();
+                average_power(worker.get_state()[Component::First.index()])
+            })
+            .collect();
+        let (regime, variation, contrast) =
+            classify(&powers, worker.get_state()[Component::First.index()]);
+        Ok(SweepPoint {
+            alpha,
+            pump,
+            regime,
+            power: powers.iter().sum::<f64>() / powers.len() as f64,
+            variation,
+            contrast,
+        })
+    }
+}
+
+///classify the regime from the `powers` over the observation window and the final `state`,
+///returning it with the power variation and the intensity contrast
+fn classify(powers: &[f64], state: &[Complex64]) -> (Regime, f64, f64) {
+    let n = powers.len() as f64;
+    let mean = powers.iter().sum::<f64>() / n;
+    let variation = if mean > 0. {
+        (powers.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / n).sqrt() / mean
+    } else {
+        0.
+    };
+    let intensity: Vec<f64> = state.iter().map(|x| x.norm_sqr()).collect();
+    let average = intensity.iter().sum::<f64>() / intensity.len() as f64;
+    let max = intensity.iter().cloned().fold(f64::MIN, f64::max);
+    let min = intensity.iter().cloned().fold(f64::MAX, f64::min);
+    let contrast = if average > 0. {
+        (max - min) / average
+    } else {
+        0.
+    };
+    let regime = if variation > STATIONARY {
+        if is_periodic(powers) {
+            Regime::Breather
+        } else {
+            Regime::Chaos
+        }
+    } else if contrast < HOMOGENEOUS {
+        Regime::Cw
+    } else {
+        let half = (max + min) / 2.;
+        let duty = intensity.iter().filter(|&&x| x > half).count() as f64 / intensity.len() as f64;
+        //rising crossings of the half maximum over the periodic round trip
+        let peaks = (0..intensity.len())
+            .filter(|&i| {
+                let prev = intensity[(i + intensity.len() - 1) % intensity.len()];
+                prev <= half && intensity[i] > half
+            })
+            .count();
+        if duty > ROLL_DUTY {
+            Regime::Rolls(peaks)
+        } else {
+            Regime::Solitons(peaks)
+        }
+    };
+    (regime, variation, contrast)
+}
+
+///whether the oscillation of `powers` concentrates around a single frequency
+fn is_periodic(powers: &[f64]) -> bool {
+    let mean = powers.iter().sum::<f64>() / powers.len() as f64;
+    let mut spectrum: Vec<Complex64> = powers
+        .iter()
+        .map(|&p| Complex64::new(p - mean, 0.))
+        .collect();
+    FftPlanner::new()
+        .plan_fft_forward(spectrum.len())
+        .process(&mut spectrum);
+    let density: Vec<f64> = spectrum[..spectrum.len() / 2 + 1]
+        .iter()
+        .map(|x| x.norm_sqr())
+        .collect();
+    let total: f64 = density[1..].iter().sum();
+    let Some(peak) = (1..density.len()).max_by(|&a, &b| density[a].total_cmp(&density[b])) else {
+        return false;
+    };
+    //the peak leaks into the neighbouring bins if not periodic in the window
+    let around: f64 = density[peak - 1..(peak + 2).min(density.len())]
+        .iter()
+        .sum();
+    total > 0. && around / total > PERIODIC
+}
+
+impl SweepMap {
+    ///write a table whose rows are
+    ///`alpha, pump, regime, count, power, variation, contrast`, headed by the seed
+    pub fn save_csv(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut content = format!(
+            "# seed {}\nalpha,pump,regime,count,power,variation,contrast\n",
+            self.seed
+        );
+        for p in self.points.iter() {
+            writeln!(
+                content,
+                "{},{},{},{},{},{},{}",
+                p.alpha,
+                p.pump,
+                p.regime.name(),
+                p.regime.count(),
+                p.power,
+                p.variation,
+                p.contrast
+            )
+            .unwrap();
+        }
+        std::fs::write(path, content)
+            .with_context(|| format!("writing sweep table {}", path.display()))
+    }
+    ///render the regime of each point as a colored cell into a png image
+    pub fn render(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let root = BitMapBackend::new(path, (800, 640)).into_drawing_area();
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+            .margin(15)
+            .x_label_area_size(40)
+            .y_label_area_size(50)
+            .build_cartesian_2d(self.alpha.range(), self.pump.range())?;
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            .x_desc("alpha")
+            .y_desc("pump")
+            .draw()?;
+        let (dx, dy) = (self.alpha.spacing() / 2., self.pump.spacing() / 2.);
+        chart.draw_series(self.points.iter().map(|p| {
+            Rectangle::new(
+                [(p.alpha - dx, p.pump - dy), (p.alpha + dx, p.pump + dy)],
+                Regime::color(p.regime.kind()).filled(),
+            )
+        }))?;
+        for (kind, name) in Regime::NAMES.iter().enumerate() {
+            if self.points.iter().any(|p| p.regime.kind() == kind) {
+                chart
+                    .draw_series(std::iter::empty::<Rectangle<(f64, f64)>>())?
+                    .label(*name)
+                    .legend(move |(x, y)| {
+                        Rectangle::new([(x, y - 5), (x + 10, y + 5)], Regime::color(kind).filled())
+                    });
+            }
+        }
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+        root.present()
+            .with_context(|| format!("writing sweep map {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+
+    const LEN: usize = 256;
+    const WINDOW: usize = 200;
+
+    fn field(intensity: impl Fn(f64) -> f64) -> Vec<Complex64> {
+        (0..LEN)
+            .map(|i| Complex64::new(intensity(2. * PI * i as f64 / LEN as f64).sqrt(), 0.))
+            .collect()
+    }
+
+    fn stationary() -> Vec<f64> {
+        vec![2.; WINDOW]
+    }
+
+    #[test]
+    fn cw() {
+        let (regime, variation, contrast) = classify(&stationary(), &field(|_| 1.5));
+        assert_eq!(regime, Regime::Cw);
+        assert_eq!(variation, 0.);
+        assert!(contrast < 1e-12);
+    }
+
+    #[test]
+    fn rolls() {
+        let state = field(|t| 1. + 0.5 * (5. * t).cos());
+        assert_eq!(classify(&stationary(), &state).0, Regime::Rolls(5));
+    }
+
+    #[test]
+    fn solitons() {
+        let sech = |x: f64| 1. / x.cosh();
+        let one = field(|t| 0.3 + 10. * sech(20. * (t - PI)).powi(2));
+        assert_eq!(classify(&stationary(), &one).0, Regime::Solitons(1));
+        let two = field(|t| 0.3 + 10. * (sech(20. * (t - 1.)) + sech(20. * (t - 4.))).powi(2));
+        assert_eq!(classify(&stationary(), &two).0, Regime::Solitons(2));
+    }
+
+    #[test]
+    fn breather() {
+        let state = field(|t| 0.3 + 10. / (20. * (t - PI)).cosh().powi(2));
+        //whole and fractional numbers of periods in the window
+        for cycles in [8., 8.5, 23.] {
+            let powers: Vec<f64> = (0..WINDOW)
+                .map(|i| 2. + 0.2 * (2. * PI * cycles * i as f64 / WINDOW as f64).sin())
+                .collect();
+            assert!(is_periodic(&powers), "{} cycles", cycles);
+            let (regime, variation, _) = classify(&powers, &state);
+            assert_eq!(regime, Regime::Breather);
+            assert!(variation > STATIONARY);
+        }
+    }
+
+    #[test]
+    fn chaos() {
+        //the logistic map in its chaotic regime
+        let mut x = 0.3;
+        let powers: Vec<f64> = (0..WINDOW)
+            .map(|_| {
+                x = 3.99 * x * (1. - x);
+                2. + x
+            })
+            .collect();
+        assert!(!is_periodic(&powers));
+        assert_eq!(classify(&powers, &field(|_| 2.)).0, Regime::Chaos);
+    }
+
+    #[test]
+    fn small_fluctuation_is_stationary() {
+        let powers: Vec<f64> = (0..WINDOW)
+            .map(|i| 2. * (1. + 1e-4 * (i as f64).sin()))
+            .collect();
+        assert_eq!(classify(&powers, &field(|_| 2.)).0, Regime::Cw);
+    }
+}
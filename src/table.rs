@@ -0,0 +1,186 @@
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::Context;
+use lle::Freq;
+use serde::{Deserialize, Serialize};
+
+use crate::{anyhow, Result};
+
+///per-mode values indexed by the mode number relative to the pumped mode
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModeTable {
+    #[serde(with = "pairs")]
+    values: BTreeMap<Freq, f64>,
+}
+
+///the values as `[mode, value]` pairs, integer map keys don't survive being buffered
+///in the internally tagged [`LossProfile`](crate::LossProfile)
+mod pairs {
+    use std::collections::BTreeMap;
+
+    use lle::Freq;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        values: &BTreeMap<Freq, f64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(values.iter())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<Freq, f64>, D::Error> {
+        let mut values = BTreeMap::new();
+        for (mode, value) in Vec::<(Freq, f64)>::deserialize(deserializer)? {
+            if values.insert(mode, value).is_some() {
+                return Err(D::Error::custom(format!("duplicated mode {}", mode)));
+            }
+        }
+        Ok(values)
+    }
+}
+
+impl ModeTable {
+    ///parse a csv file whose rows are `mode number, value`,
+    ///empty lines, lines starting with `#` and a header line are skipped
+    pub fn from_csv(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("reading mode table {}", path.display()))?;
+        Self::parse_csv(&content).with_context(|| format!("parsing {}", path.display()))
+    }
+    pub fn parse_csv(content: &str) -> Result<Self> {
+        let mut values = BTreeMap::new();
+        let mut first = true;
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let header = std::mem::replace(&mut first, false);
+            let mut fields = line.split(',').map(str::trim);
+            let (mode, value) = match (fields.next(), fields.next(), fields.next()) {
+                (Some(mode), Some(value), None) => (mode, value),
+                _ => {
+                    return Err(anyhow!(
+                        "line {}: expected 2 columns, got '{}'",
+                        i + 1,
+                        line
+                    ))
+                }
+            };
+            let mode = match mode.parse::<Freq>() {
+                Ok(mode) => mode,
+                //header line
+                Err(_) if header && value.parse::<f64>().is_err() => continue,
+                Err(e) => {
+                    return Err(anyhow!(
+                        "line {}: illegal mode number '{}': {}",
+                        i + 1,
+                        mode,
+                        e
+                    ))
+                }
+            };
+            let value = value
+                .parse::<f64>()
+                .map_err(|e| anyhow!("line {}: illegal value '{}': {}", i + 1, value, e))?;
+            if values.insert(mode, value).is_some() {
+                return Err(anyhow!("line {}: duplicated mode {}", i + 1, mode));
+            }
+        }
+        Ok(Self { values })
+    }
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+    pub fn get(&self, mode: Freq) -> Option<f64> {
+        self.values.get(&mode).copied()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (Freq, f64)> + '_ {
+        self.values.iter().map(|(&m, &v)| (m, v))
+    }
+    ///check the table has exactly one entry for each mode simulated with `modes` points
+    pub fn check_modes(&self, modes: usize) -> Result<()> {
+        if self.len() != modes {
+            return Err(anyhow!(
+                "mode table has {} entries while simulating {} modes",
+                self.len(),
+                modes
+            ));
+        }
+        let (lower, upper) = mode_range(modes);
+        match self.values.keys().find(|&&m| m < lower || m > upper) {
+            Some(m) => Err(anyhow!(
+                "mode {} out of the simulated range [{}, {}]",
+                m,
+                lower,
+                upper
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+///the lowest and highest relative mode number simulated with `modes` points
+pub fn mode_range(modes: usize) -> (Freq, Freq) {
+    (-((modes / 2) as Freq), ((modes.max(1) - 1) / 2) as Freq)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_with_header_and_comments() {
+        let table =
+            ModeTable::parse_csv("# measured dint\nmode, value\n\n-1, 0.5\n0,0\n 1 , 0.25 \n")
+                .unwrap();
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.get(-1), Some(0.5));
+        assert_eq!(table.get(1), Some(0.25));
+        assert_eq!(table.get(2), None);
+        assert!(table.check_modes(3).is_ok());
+        assert!(table.check_modes(4).is_err());
+    }
+
+    #[test]
+    fn malformed_rows() {
+        for (content, message) in [
+            ("0,1,2", "line 1: expected 2 columns"),
+            ("0", "line 1: expected 2 columns"),
+            ("0,1\nx,2", "line 2: illegal mode number 'x'"),
+            ("0,1\n1,y", "line 2: illegal value 'y'"),
+            (
+                "mode,value\nmode,value",
+                "line 2: illegal mode number 'mode'",
+            ),
+            ("0.5,1", "line 1: illegal mode number '0.5'"),
+        ] {
+            let error = ModeTable::parse_csv(content)
+                .expect_err(content)
+                .to_string();
+            assert!(error.contains(message), "{}: {}", content, error);
+        }
+    }
+
+    #[test]
+    fn duplicate_rows() {
+        let error = ModeTable::parse_csv("0,1\n1,2\n# comment\n0,3")
+            .expect_err("duplicated mode")
+            .to_string();
+        assert!(error.contains("line 4: duplicated mode 0"), "{}", error);
+    }
+
+    #[test]
+    fn modes_out_of_range() {
+        let table = ModeTable::parse_csv("-1,0\n0,0\n2,0").unwrap();
+        assert!(table.check_modes(3).is_err());
+        assert_eq!(mode_range(4), (-2, 1));
+        assert_eq!(mode_range(5), (-2, 2));
+    }
+}
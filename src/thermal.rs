@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+///thermo-optic effect of one component, the temperature `T` follows
+///`τ * dT/dt = P - T` driven by the average intracavity power `P`,
+///shifting the detuning by `coefficient * T`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Thermal {
+    ///thermal relaxation time, much longer than the photon lifetime
+    pub time: f64,
+    ///detuning shift per unit temperature, negative for the red shift of the resonance on heating
+    pub coefficient: f64,
+}
+
+impl Default for Thermal {
+    fn default() -> Self {
+        Self {
+            time: 1e3,
+            coefficient: -0.5,
+        }
+    }
+}
+
+impl Thermal {
+    ///temperature after `duration` starting from `temperature` under constant `power`
+    pub fn relax(&self, temperature: f64, power: f64, duration: f64) -> f64 {
+        power + (temperature - power) * (-duration / self.time).exp()
+    }
+    pub fn detuning_shift(&self, temperature: f64) -> f64 {
+        self.coefficient * temperature
+    }
+}